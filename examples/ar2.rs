@@ -0,0 +1,32 @@
+//! Estimates the coefficients of an AR(2) process x(i) = a₁ x(i-1) + a₂ x(i-2) + ε(i) by
+//! predicting each sample from the two previous ones.
+
+extern crate recless;
+
+use recless::{DelayLine, Rls};
+
+fn main() {
+    let (a1, a2) = (0.6, -0.3);
+
+    // A small linear congruential generator for uniform noise in [-0.5, 0.5).
+    let mut state: u64 = 42;
+    let mut noise = || {
+        state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (state >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+    };
+
+    let mut rls = Rls::new(0.01, 1.0, 2);
+    let mut past = DelayLine::new(2);
+
+    for _ in 0..10000 {
+        let u = past.as_array();
+        let x = a1 * u[0] + a2 * u[1] + noise();
+
+        rls.update(&past.as_array(), x);
+        past.push(x);
+    }
+
+    let weight = rls.weight_ref();
+    println!("true coefficients:      a₁ = {:.3}, a₂ = {:.3}", a1, a2);
+    println!("estimated coefficients: a₁ = {:.3}, a₂ = {:.3}", weight[0], weight[1]);
+}
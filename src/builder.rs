@@ -0,0 +1,231 @@
+use ndarray::prelude::*;
+
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+use {ErrorWindow, Rls, RlsError, WeightSmoothing};
+
+/// A builder for validated construction of [`Rls`](struct.Rls.html) objects.
+///
+/// The initialization factor δ, the forgetting factor λ, and either the number of taps or an
+/// initial weight vector have to be set before calling [`build`](#method.build).
+#[derive(Clone,Debug)]
+pub struct RlsBuilder<F> {
+    initialization_factor: Option<F>,
+    forgetting_factor: Option<F>,
+    taps: Option<usize>,
+    weight: Option<Array1<F>>,
+    leakage: Option<F>,
+    error_window: usize,
+    record_history: bool,
+    min_denominator: Option<F>,
+    weight_smoothing: Option<F>,
+}
+
+impl<F> Default for RlsBuilder<F> {
+    fn default() -> Self {
+        RlsBuilder {
+            initialization_factor: None,
+            forgetting_factor: None,
+            taps: None,
+            weight: None,
+            leakage: None,
+            error_window: 0,
+            record_history: false,
+            min_denominator: None,
+            weight_smoothing: None,
+        }
+    }
+}
+
+impl<F: NdFloat> RlsBuilder<F> {
+
+    /// Constructs a new builder without any parameters set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the initialization factor δ.
+    pub fn initialization_factor(mut self, initialization_factor: F) -> Self {
+        self.initialization_factor = Some(initialization_factor);
+        self
+    }
+
+    /// Sets the forgetting factor λ.
+    pub fn forgetting_factor(mut self, forgetting_factor: F) -> Self {
+        self.forgetting_factor = Some(forgetting_factor);
+        self
+    }
+
+    /// Sets the number of taps n, starting from a zero weight vector.
+    pub fn taps(mut self, n: usize) -> Self {
+        self.taps = Some(n);
+        self.weight = None;
+        self
+    }
+
+    /// Sets a pre-defined weight vector w, which also determines the number of taps.
+    pub fn weight(mut self, weight: Array1<F>) -> Self {
+        self.taps = None;
+        self.weight = Some(weight);
+        self
+    }
+
+    /// Sets the leakage γ, see [`Rls::with_leakage`](struct.Rls.html#method.with_leakage).
+    pub fn leakage(mut self, leakage: F) -> Self {
+        self.leakage = Some(leakage);
+        self
+    }
+
+    /// Keeps the last K prior errors for [`Rls::windowed_rmse`](struct.Rls.html#method.windowed_rmse).
+    /// With K = 0, the default, no errors are kept and no buffer is allocated.
+    pub fn error_window(mut self, k: usize) -> Self {
+        self.error_window = k;
+        self
+    }
+
+    /// Records the weight vector after each update for
+    /// [`Rls::weight_history`](struct.Rls.html#method.weight_history), e.g. to analyze
+    /// convergence after training. Off by default, since the history grows by a copy of the
+    /// n weights per update without bound.
+    pub fn record_history(mut self, record_history: bool) -> Self {
+        self.record_history = record_history;
+        self
+    }
+
+    /// Sets the smallest gain denominator an update is performed with, see
+    /// [`Rls::set_min_denominator`](struct.Rls.html#method.set_min_denominator).
+    ///
+    /// [`build`](#method.build) returns an error if `min_denominator` is negative.
+    pub fn min_denominator(mut self, min_denominator: F) -> Self {
+        self.min_denominator = Some(min_denominator);
+        self
+    }
+
+    /// Tracks an exponentially smoothed weight vector with smoothing factor α for
+    /// [`Rls::smoothed_weight`](struct.Rls.html#method.smoothed_weight).
+    ///
+    /// [`build`](#method.build) returns an error unless 0 < α ≤ 1.
+    pub fn weight_smoothing(mut self, alpha: F) -> Self {
+        self.weight_smoothing = Some(alpha);
+        self
+    }
+
+    /// Constructs the Rls object.
+    ///
+    /// Returns an error if a required parameter is missing, if λ ≤ 0 or λ > 1, if δ ≤ 0, if the
+    /// filter has no taps, if the minimum denominator is negative, or unless the smoothing factor
+    /// of the weights satisfies 0 < α ≤ 1.
+    pub fn build(self) -> Result<Rls<F>, RlsError> {
+        let initialization_factor = self.initialization_factor
+            .ok_or(RlsError::MissingParameter("initialization_factor"))?;
+        let forgetting_factor = self.forgetting_factor
+            .ok_or(RlsError::MissingParameter("forgetting_factor"))?;
+        let weight = match (self.weight, self.taps) {
+            (Some(weight), _) => weight,
+            (None, Some(n)) => Array1::zeros(n),
+            (None, None) => return Err(RlsError::MissingParameter("taps")),
+        };
+
+        let mut rls = Rls::try_with_weight(initialization_factor, forgetting_factor, weight)?;
+        if let Some(leakage) = self.leakage {
+            rls.leakage = leakage;
+        }
+        if self.error_window > 0 {
+            rls.error_window = Some(ErrorWindow::new(self.error_window));
+        }
+        if let Some(min_denominator) = self.min_denominator {
+            if min_denominator.is_nan() || min_denominator < F::zero() {
+                return Err(RlsError::InvalidMinDenominator);
+            }
+            rls.min_denominator = min_denominator;
+        }
+        if self.record_history {
+            rls.weight_history = Some(Vec::new());
+        }
+        if let Some(alpha) = self.weight_smoothing {
+            if alpha.is_nan() || alpha <= F::zero() || alpha > F::one() {
+                return Err(RlsError::InvalidSmoothingFactor);
+            }
+            rls.weight_smoothing = Some(WeightSmoothing {
+                alpha,
+                weight: rls.weight.clone(),
+            });
+        }
+        Ok(rls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::Lcg;
+
+    fn builder() -> RlsBuilder<f64> {
+        RlsBuilder::new()
+            .initialization_factor(0.1)
+            .forgetting_factor(1.0)
+            .taps(1)
+    }
+
+    #[test]
+    fn build_rejects_negative_min_denominator() {
+        assert_eq!(builder().min_denominator(-1e-6).build().unwrap_err(),
+            RlsError::InvalidMinDenominator);
+        assert_eq!(builder().min_denominator(f64::NAN).build().unwrap_err(),
+            RlsError::InvalidMinDenominator);
+        assert!(builder().min_denominator(0.0).build().is_ok());
+    }
+
+    #[test]
+    fn min_denominator_guard_keeps_filter_finite() {
+        // Emulate an inverse correlation matrix made indefinite by round-off, for which the
+        // gain denominator c = 1 + u P u of the input u = 1 vanishes.
+        let mut guarded = builder().min_denominator(1e-6).build().unwrap();
+        guarded.inverse_correlation.fill(-1.0);
+        let mut unguarded = guarded.clone();
+        unguarded.min_denominator = 0.0;
+
+        let original = guarded.clone();
+        assert_eq!(guarded.try_update(&arr1(&[1.0]), 1.0), Err(RlsError::SmallDenominator));
+        assert_eq!(guarded, original);
+
+        unguarded.update(&arr1(&[1.0]), 1.0);
+        assert!(unguarded.weight_ref().iter().any(|w| !w.is_finite()));
+    }
+
+    #[test]
+    fn build_rejects_invalid_smoothing_factor() {
+        for &alpha in &[0.0, -0.5, 1.5, f64::NAN] {
+            assert_eq!(builder().weight_smoothing(alpha).build().unwrap_err(),
+                RlsError::InvalidSmoothingFactor);
+        }
+        assert!(builder().weight_smoothing(1.0).build().is_ok());
+    }
+
+    #[test]
+    fn smoothed_weight_has_lower_variance() {
+        fn variance(x: &[f64]) -> f64 {
+            let mean = x.iter().sum::<f64>() / x.len() as f64;
+            x.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / x.len() as f64
+        }
+
+        let builder = RlsBuilder::new().initialization_factor(0.1).forgetting_factor(0.95).taps(2);
+        let mut smoothed = builder.clone().weight_smoothing(0.05).build().unwrap();
+        let mut unsmoothed = builder.weight_smoothing(1.0).build().unwrap();
+        let mut rng = Lcg::new(98);
+        let (mut raw, mut smooth) = (vec![], vec![]);
+        for i in 0..3000 {
+            let input = rng.array(2);
+            let target = 2.0 * input[0] - input[1] + 0.5 * rng.next();
+            smoothed.update(&input, target);
+            unsmoothed.update(&input, target);
+            assert_eq!(unsmoothed.smoothed_weight(), unsmoothed.weight_ref());
+            if i > 500 {
+                raw.push(smoothed.weight_ref()[0]);
+                smooth.push(smoothed.smoothed_weight()[0]);
+            }
+        }
+        assert!(variance(&smooth) < 0.5 * variance(&raw));
+    }
+}
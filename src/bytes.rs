@@ -0,0 +1,213 @@
+use ndarray::prelude::*;
+
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+use core::mem;
+
+use {Rls, RlsError};
+
+/// The magic bytes at the start of the byte layout.
+const MAGIC: &[u8; 4] = b"RCLS";
+
+/// The version of the byte layout.
+const VERSION: u8 = 1;
+
+/// The length of the header: magic, version, element size, flags, and number of taps.
+const HEADER_LEN: usize = 4 + 1 + 1 + 1 + 8;
+
+/// The flag of a filter with a [bias term](struct.Rls.html#method.with_bias).
+const FLAG_BIAS: u8 = 1;
+
+impl<F: NdFloat> Rls<F> {
+
+    /// Encodes the inverse forgetting factor λ^{-1}, the weight vector w, and the inverse
+    /// correlation matrix P in a compact, versioned byte layout, e.g. for a binary protocol.
+    ///
+    /// The layout is the magic bytes `RCLS`, the version 1 as a byte, the size of the element
+    /// type in bytes (4 for `f32`, 8 for `f64`), a flags byte whose least significant bit is set
+    /// for a filter with a [bias term](#method.with_bias), and the number of taps n, including
+    /// the bias, as a little-endian `u64`, followed by λ^{-1}, the n weights, and the n × n
+    /// elements of P in row-major order, each as a little-endian IEEE 754 floating point number.
+    /// Like [`into_parts`](#method.into_parts), this omits the other parameters and the state of
+    /// the filter, see [`from_bytes`](#method.from_bytes).
+    ///
+    /// **Panics** if the element type is neither `f32` nor `f64` sized.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let size = mem::size_of::<F>();
+        assert!(size == 4 || size == 8, "unsupported element size {}", size);
+
+        let n = self.weight.len();
+        let mut bytes = Vec::with_capacity(HEADER_LEN + (1 + n + n * n) * size);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(size as u8);
+        bytes.push(if self.bias { FLAG_BIAS } else { 0 });
+        bytes.extend_from_slice(&(n as u64).to_le_bytes());
+
+        let values = Some(&self.inv_forgetting_factor).into_iter()
+            .chain(self.weight.iter())
+            .chain(self.inverse_correlation.iter());
+        for &x in values {
+            if size == 4 {
+                bytes.extend_from_slice(&x.to_f32().unwrap().to_le_bytes());
+            } else {
+                bytes.extend_from_slice(&x.to_f64().unwrap().to_le_bytes());
+            }
+        }
+        bytes
+    }
+
+    /// Decodes a filter previously encoded by [`to_bytes`](#method.to_bytes).
+    ///
+    /// The filter is reconstructed by [`from_parts`](#method.from_parts), so apart from λ, w, P,
+    /// and the bias term it starts out with the defaults of a new filter, and its scratch
+    /// buffers are allocated anew.
+    ///
+    /// Returns [`RlsError::InvalidBytes`] if `bytes` does not start with a valid header for the
+    /// element type, if it has unknown flags or a bias term without taps, or if its length does
+    /// not match the number of taps, and the errors of `from_parts` for an invalid forgetting
+    /// factor or an asymmetric P.
+    ///
+    /// [`RlsError::InvalidBytes`]: enum.RlsError.html#variant.InvalidBytes
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, RlsError> {
+        let size = mem::size_of::<F>();
+
+        if bytes.len() < HEADER_LEN {
+            return Err(RlsError::InvalidBytes("truncated header"));
+        }
+        let (header, body) = bytes.split_at(HEADER_LEN);
+        if &header[..4] != MAGIC {
+            return Err(RlsError::InvalidBytes("missing magic bytes"));
+        }
+        if header[4] != VERSION {
+            return Err(RlsError::InvalidBytes("unsupported version"));
+        }
+        if header[5] as usize != size {
+            return Err(RlsError::InvalidBytes("element size does not match"));
+        }
+
+        let flags = header[6];
+        if flags & !FLAG_BIAS != 0 {
+            return Err(RlsError::InvalidBytes("unknown flags"));
+        }
+
+        let mut n_bytes = [0; 8];
+        n_bytes.copy_from_slice(&header[7..]);
+        let n = u64::from_le_bytes(n_bytes);
+        if n > usize::MAX as u64 {
+            return Err(RlsError::InvalidBytes("number of taps is too large"));
+        }
+        let n = n as usize;
+        let bias = flags & FLAG_BIAS != 0;
+        if bias && n == 0 {
+            return Err(RlsError::InvalidBytes("bias term without taps"));
+        }
+
+        // The number of values is 1 + n + n², checked for overflow.
+        let expected_len = n.checked_mul(n)
+            .and_then(|len| len.checked_add(n + 1))
+            .and_then(|len| len.checked_mul(size));
+        if expected_len != Some(body.len()) {
+            return Err(RlsError::InvalidBytes("length does not match the number of taps"));
+        }
+
+        let mut values = body.chunks(size).map(|chunk| {
+            if size == 4 {
+                let mut x = [0; 4];
+                x.copy_from_slice(chunk);
+                F::from(f32::from_le_bytes(x)).unwrap()
+            } else {
+                let mut x = [0; 8];
+                x.copy_from_slice(chunk);
+                F::from(f64::from_le_bytes(x)).unwrap()
+            }
+        });
+        let inv_forgetting_factor = values.next().unwrap();
+        let weight = Array1::from_iter(values.by_ref().take(n));
+        let inverse_correlation = Array2::from_shape_vec((n, n), values.collect()).unwrap();
+
+        let mut rls = Rls::from_parts(inv_forgetting_factor, weight, inverse_correlation)?;
+        if bias {
+            rls.enable_bias();
+        }
+        Ok(rls)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::Lcg;
+
+    fn trained(mut rls: Rls<f64>) -> Rls<f64> {
+        let mut rng = Lcg::new(88);
+        for _ in 0..20 {
+            let input = rng.array(3);
+            let target = input[0] - 0.5 * input[2] + 0.1;
+            rls.update(&input, target);
+        }
+        rls
+    }
+
+    fn assert_round_trip(rls: &Rls<f64>) {
+        let mut decoded = Rls::<f64>::from_bytes(&rls.to_bytes()).unwrap();
+        assert_eq!(decoded.forgetting_factor(), rls.forgetting_factor());
+        assert_eq!(decoded.weight_ref(), rls.weight_ref());
+        assert_eq!(decoded.inverse_correlation_ref(), rls.inverse_correlation_ref());
+        assert_eq!(decoded.has_bias(), rls.has_bias());
+
+        let mut rls = rls.clone();
+        let input = arr1(&[0.3, -0.2, 0.1]);
+        assert_eq!(decoded.predict(&input), rls.predict(&input));
+        assert_eq!(decoded.update(&input, 1.0), rls.update(&input, 1.0));
+        assert_eq!(decoded.weight_ref(), rls.weight_ref());
+    }
+
+    #[test]
+    fn round_trip_preserves_filter() {
+        assert_round_trip(&trained(Rls::new(0.01, 0.97, 3)));
+        assert_round_trip(&trained(Rls::with_bias(0.01, 0.97, 3)));
+
+        let rls = trained(Rls::new(0.01, 0.97, 3)).to_f32();
+        let decoded = Rls::<f32>::from_bytes(&rls.to_bytes()).unwrap();
+        assert_eq!(decoded.weight_ref(), rls.weight_ref());
+        assert_eq!(decoded.inverse_correlation_ref(), rls.inverse_correlation_ref());
+    }
+
+    #[test]
+    fn rejects_truncated_and_garbage_bytes() {
+        let bytes = trained(Rls::with_bias(0.01, 0.97, 3)).to_bytes();
+        assert_eq!(bytes.len(), HEADER_LEN + (1 + 4 + 16) * 8);
+        let invalid = |bytes: &[u8]| matches!(Rls::<f64>::from_bytes(bytes), Err(RlsError::InvalidBytes(_)));
+
+        assert!(invalid(&[]));
+        assert!(invalid(&bytes[..5]));
+        assert!(invalid(&bytes[..bytes.len() - 1]));
+        assert!(invalid(&[bytes.as_slice(), &[0]].concat()));
+        assert!(invalid(&Lcg::new(0).array(64).iter().map(|x| (x * 256.0) as u8).collect::<Vec<_>>()));
+        assert!(matches!(Rls::<f32>::from_bytes(&bytes), Err(RlsError::InvalidBytes(_))));
+
+        let corrupt = |index: usize, byte: u8| {
+            let mut corrupted = bytes.clone();
+            corrupted[index] = byte;
+            corrupted
+        };
+        assert!(invalid(&corrupt(0, b'X')));
+        assert!(invalid(&corrupt(4, VERSION + 1)));
+        assert!(invalid(&corrupt(6, 2)));
+
+        let mut too_many_taps = bytes.clone();
+        too_many_taps[7..HEADER_LEN].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(invalid(&too_many_taps));
+
+        let mut zero_inv_forgetting_factor = bytes.clone();
+        zero_inv_forgetting_factor[HEADER_LEN..HEADER_LEN + 8].copy_from_slice(&0.0f64.to_le_bytes());
+        assert_eq!(Rls::<f64>::from_bytes(&zero_inv_forgetting_factor).unwrap_err(),
+            RlsError::InvalidForgettingFactor);
+
+        let mut biased_without_taps = Rls::<f64>::new(0.01, 0.97, 0).to_bytes();
+        biased_without_taps[6] = FLAG_BIAS;
+        assert!(invalid(&biased_without_taps));
+    }
+}
@@ -0,0 +1,245 @@
+use ndarray::prelude::*;
+use ndarray::{Data, Zip};
+use ndarray::linalg::general_mat_vec_mul;
+use num_complex::Complex;
+
+#[cfg(feature = "blas")]
+use same_type;
+#[cfg(feature = "blas")]
+use core::slice;
+
+/// The parameters of a complex-valued recursive least squares algorithm.
+///
+/// The filter output is y(i) = w(i)ᵀ u(i), i.e. an ordinary (unconjugated) FIR convolution, so
+/// that the weights converge to the taps of the channel being identified. The inverse
+/// correlation matrix P(i) is Hermitian, and the gain and its update use the conjugate inner
+/// product uᴴ P u and the conjugate outer product k (Pᴴ u)ᴴ.
+///
+/// This is a separate type rather than an `impl Rls<Complex<F>>`, because inherent methods of
+/// [`Rls`](struct.Rls.html) for complex elements would overlap with the generic ones.
+///
+/// *Note:* Unlike the real-valued filters, this type cannot be serialized, since `num-complex`
+/// 0.1 does not support serde 1.0.
+#[derive(Clone,Debug)]
+pub struct ComplexRls<F> {
+
+    /// The inverse forgetting factor λ^{-1}.
+    inv_forgetting_factor: F,
+
+    /// The gain vector used during the update of the inverse correlation matrix P(i) and the
+    /// (tap) weight vector w(i).
+    gain: Array1<Complex<F>>,
+
+    /// The Hermitian *inverse correlation matrix*, initialized as P = δ^{-1} · 𝟙.
+    inverse_correlation: Array2<Complex<F>>,
+
+    /// The *(tap) weight vector* w(i).
+    weight: Array1<Complex<F>>,
+
+    /// The prior error, calculated as the difference between the desired output and the filter
+    /// output before an update.
+    prior_error: Complex<F>,
+
+    // Scratch vector used for the update of the inverse correlation matrix.
+    temp_vec: Array1<Complex<F>>,
+}
+
+impl<F: NdFloat> ComplexRls<F> {
+
+    /// Constructs a new ComplexRls object with initialization factor δ and a weight vector of
+    /// length n.
+    pub fn new(initialization_factor: F, forgetting_factor: F, n: usize) -> Self {
+        let weight = Array1::from_elem(n, Complex::new(F::zero(), F::zero()));
+
+        ComplexRls::with_weight(initialization_factor, forgetting_factor, weight)
+    }
+
+    /// Constructs a new ComplexRls object with initialization factor δ and pre-defined weight
+    /// vector w.
+    pub fn with_weight(initialization_factor: F, forgetting_factor: F, weight: Array1<Complex<F>>) -> Self {
+        let one = F::one();
+        let zero = Complex::new(F::zero(), F::zero());
+
+        let n = weight.len();
+
+        let inv_forgetting_factor = one / forgetting_factor;
+
+        let gain = Array1::from_elem(n, zero);
+        let prior_error = zero;
+
+        let mut inverse_correlation = Array2::from_elem([n, n], zero);
+        inverse_correlation.diag_mut().fill(Complex::new(one/initialization_factor, F::zero()));
+
+        let temp_vec = Array1::from_elem(n, zero);
+
+        ComplexRls {
+            inv_forgetting_factor,
+            gain,
+            inverse_correlation,
+            weight,
+            prior_error,
+            temp_vec,
+        }
+    }
+
+    /// Computes the filter output y(i) = w(i)ᵀ u(i) without updating the filter state.
+    ///
+    /// **Panics** if the length of `input` does not match the length of the weight vector.
+    pub fn predict<S>(&self, input: &ArrayBase<S, Ix1>) -> Complex<F>
+        where S: Data<Elem = Complex<F>>
+    {
+        assert_eq!(input.len(), self.weight.len(),
+            "input of length {} does not match weight of length {}",
+            input.len(), self.weight.len());
+        self.weight.dot(input)
+    }
+
+    /// Performs a recursive update of inverse correlation matrix and weight vector, returning
+    /// the prior error.
+    ///
+    /// **Panics** if the length of `input` does not match the length of the weight vector.
+    pub fn update<S>(&mut self, input: &ArrayBase<S, Ix1>, target: Complex<F>) -> Complex<F>
+        where S: Data<Elem = Complex<F>>
+    {
+        assert_eq!(input.len(), self.weight.len(),
+            "input of length {} does not match weight of length {}",
+            input.len(), self.weight.len());
+
+        let one = Complex::new(F::one(), F::zero());
+        let zero = Complex::new(F::zero(), F::zero());
+
+        // k(i) = P(i-1) u(i) / (λ^{-1} + u(i)ᴴ P(i-1) u(i)), where the denominator is real
+        // because P is Hermitian.
+        general_mat_vec_mul(
+            one,
+            &self.inverse_correlation,
+            input,
+            zero,
+            &mut self.gain
+        );
+        let quadratic_form = input.iter()
+            .zip(self.gain.iter())
+            .fold(F::zero(), |acc, (u, p)| acc + (u.conj() * p).re);
+        let c = self.inv_forgetting_factor + quadratic_form;
+        self.gain.mapv_inplace(|k| k / c);
+
+        // Calculate the prior error using the not yet updated tap weight.
+        self.prior_error = target - self.weight.dot(input);
+
+        // Update the tap weight as w(i) = w(i-1) + k(i)* e(i), since the output is unconjugated.
+        let prior_error = self.prior_error;
+        Zip::from(&mut self.weight)
+            .and(&self.gain)
+            .apply(|w, &k| *w += k.conj() * prior_error);
+
+        // Update P(i) = λ^{-1} (P(i-1) - k(i) (P(i-1)ᴴ u(i))ᴴ).
+        self.temp_vec.fill(zero);
+        for (row, &u) in self.inverse_correlation.outer_iter().zip(input.iter()) {
+            Zip::from(&mut self.temp_vec)
+                .and(&row)
+                .apply(|t, &p| *t += p.conj() * u);
+        }
+        rank1_update_conj(&mut self.inverse_correlation, &self.gain, &self.temp_vec);
+        let inv_forgetting_factor = self.inv_forgetting_factor;
+        self.inverse_correlation.mapv_inplace(|p| p * inv_forgetting_factor);
+
+        self.prior_error
+    }
+}
+
+/// Performs the conjugate rank-1 update A ← A - x yᴴ.
+///
+/// *Note:* If the `blas` feature is enabled, uses blas `?gerc` for elements of
+/// `Complex<f32>, Complex<f64>`.
+fn rank1_update_conj<F: NdFloat>(
+    a: &mut Array2<Complex<F>>,
+    x: &Array1<Complex<F>>,
+    y: &Array1<Complex<F>>,
+) {
+    #[cfg(feature = "blas")]
+    macro_rules! gerc {
+        ($t:ty, $gerc:path) => {
+            if same_type::<F, $t>() {
+                let (m, n) = a.dim();
+                let lda = a.strides()[0];
+                let x = x.as_slice().unwrap();
+                let y = y.as_slice().unwrap();
+                let a = a.as_slice_mut().unwrap();
+                unsafe {
+                    $gerc(
+                        blas::c::Layout::RowMajor,
+                        m as i32,
+                        n as i32,
+                        &[Complex::new(-1.0, 0.0)],
+                        slice::from_raw_parts(x.as_ptr() as *const Complex<$t>, x.len()),
+                        1,
+                        slice::from_raw_parts(y.as_ptr() as *const Complex<$t>, y.len()),
+                        1,
+                        slice::from_raw_parts_mut(a.as_mut_ptr() as *mut Complex<$t>, a.len()),
+                        lda as i32,
+                    );
+                }
+                return;
+            }
+        }
+    }
+    #[cfg(feature = "blas")]
+    gerc!(f32, blas::c::cgerc);
+    #[cfg(feature = "blas")]
+    gerc!(f64, blas::c::zgerc);
+
+    for (mut row, &x) in a.outer_iter_mut().zip(x.iter()) {
+        Zip::from(&mut row)
+            .and(y)
+            .apply(|a, &y| *a -= x * y.conj());
+    }
+}
+
+impl<T> ComplexRls<T> {
+
+    /// Returns a reference to the gain vector.
+    pub fn gain_ref(&self) -> &Array1<Complex<T>> {
+        &self.gain
+    }
+
+    /// Returns a reference to the inverse correlation matrix.
+    pub fn inverse_correlation_ref(&self) -> &Array2<Complex<T>> {
+        &self.inverse_correlation
+    }
+
+    /// Returns a reference to the inverse forgetting factor.
+    pub fn inv_forgetting_factor_ref(&self) -> &T {
+        &self.inv_forgetting_factor
+    }
+
+    /// Returns a reference to the (tap) weight vector.
+    pub fn weight_ref(&self) -> &Array1<Complex<T>> {
+        &self.weight
+    }
+
+    /// Returns a reference to the prior error.
+    pub fn prior_error_ref(&self) -> &Complex<T> {
+        &self.prior_error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::Lcg;
+
+    #[test]
+    fn weights_converge_to_complex_channel() {
+        let mut rng = Lcg::new(22);
+        let channel = arr1(&[Complex::new(1.0, -0.5), Complex::new(0.25, 2.0), Complex::new(-1.0, 0.0)]);
+        let mut rls = ComplexRls::new(0.01, 1.0, 3);
+        for _ in 0..200 {
+            let input = Array1::from_iter((0..3).map(|_| Complex::new(rng.next(), rng.next())));
+            let target = channel.dot(&input);
+            rls.update(&input, target);
+        }
+        for (w, h) in rls.weight_ref().iter().zip(channel.iter()) {
+            assert!((w - h).norm() < 1e-3);
+        }
+    }
+}
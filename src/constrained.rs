@@ -0,0 +1,173 @@
+use ndarray::prelude::*;
+use ndarray::Data;
+
+use {solve_positive_definite, Rls, RlsError};
+
+/// The parameters of a linearly constrained recursive least squares algorithm.
+///
+/// The weight vector satisfies the linear constraints Cᵀ w(i) = g at all times, e.g. a unit gain
+/// in a look direction for beamforming. C is the n × m constraint matrix with m ≤ n linearly
+/// independent columns, and g the response vector of length m. After each standard update of the
+/// wrapped [`Rls`](struct.Rls.html), the weight vector is projected back onto the constraint set
+/// with the projection weighted by the inverse correlation matrix P(i),
+///
+/// w(i) ← w(i) + P(i) C (Cᵀ P(i) C)^{-1} (g - Cᵀ w(i)),
+///
+/// which gives the constrained least squares solution for the data seen so far.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "F: ::serde::Deserialize<'de> + Default")))]
+#[derive(Clone,Debug)]
+pub struct ConstrainedRls<F> {
+
+    /// The unconstrained filter, holding the projected weight vector.
+    rls: Rls<F>,
+
+    /// The constraint matrix C of shape `(n_taps, n_constraints)`.
+    constraint: Array2<F>,
+
+    /// The response vector g of length `n_constraints`.
+    response: Array1<F>,
+}
+
+impl<F: NdFloat> ConstrainedRls<F> {
+
+    /// Constructs a new ConstrainedRls object with initialization factor δ, the constraint
+    /// matrix C of shape `(n_taps, n_constraints)`, and the response vector g.
+    ///
+    /// The weight vector starts out as the projection of zero onto the constraint set.
+    ///
+    /// Returns an error if the length of g does not match the number of columns of C, or if the
+    /// columns of C are linearly dependent.
+    pub fn new(initialization_factor: F, forgetting_factor: F, constraint: Array2<F>, response: Array1<F>)
+        -> Result<Self, RlsError>
+    {
+        if response.len() != constraint.cols() {
+            return Err(RlsError::DimensionMismatch {
+                expected: constraint.cols(),
+                got: response.len(),
+            });
+        }
+
+        let rls = Rls::new(initialization_factor, forgetting_factor, constraint.rows());
+        let mut constrained = ConstrainedRls {
+            rls,
+            constraint,
+            response,
+        };
+        if !constrained.project() {
+            return Err(RlsError::SingularConstraint);
+        }
+        Ok(constrained)
+    }
+
+    /// Computes the filter output y(i) = w(i) · u(i) without updating the filter state.
+    ///
+    /// **Panics** if the length of `input` does not match the length of the weight vector.
+    pub fn predict<S>(&self, input: &ArrayBase<S, Ix1>) -> F
+        where S: Data<Elem = F>
+    {
+        self.rls.predict(input)
+    }
+
+    /// Performs a recursive update of inverse correlation matrix and weight vector, followed by
+    /// the projection of the weight vector onto the constraint set, returning the prior error.
+    ///
+    /// **Panics** if the update fails, see [`Rls::try_update`](struct.Rls.html#method.try_update),
+    /// or if Cᵀ P(i) C is no longer positive definite due to round-off.
+    pub fn update<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F) -> F
+        where S: Data<Elem = F>
+    {
+        let prior_error = self.rls.update(input, target);
+        if !self.project() {
+            panic!("{}", RlsError::SingularConstraint);
+        }
+        prior_error
+    }
+
+    /// Projects the weight vector onto the constraint set, returning `false` if Cᵀ P C is not
+    /// positive definite.
+    fn project(&mut self) -> bool {
+        let p_c = self.rls.inverse_correlation_ref().dot(&self.constraint);
+        let gram = self.constraint.t().dot(&p_c);
+
+        let mut residual = Array2::zeros([self.response.len(), 1]);
+        {
+            let mut column = residual.column_mut(0);
+            column.assign(&self.response);
+            column -= &self.constraint.t().dot(self.rls.weight_ref());
+        }
+        if !solve_positive_definite(&gram, &mut residual) {
+            return false;
+        }
+
+        let correction = p_c.dot(&residual.column(0));
+        *self.rls.weight_mut() += &correction;
+        true
+    }
+}
+
+impl<T> ConstrainedRls<T> {
+
+    /// Returns a reference to the constraint matrix C.
+    pub fn constraint_ref(&self) -> &Array2<T> {
+        &self.constraint
+    }
+
+    /// Returns a reference to the response vector g.
+    pub fn response_ref(&self) -> &Array1<T> {
+        &self.response
+    }
+
+    /// Returns a reference to the gain vector.
+    pub fn gain_ref(&self) -> &Array1<T> {
+        self.rls.gain_ref()
+    }
+
+    /// Returns a reference to the inverse correlation matrix.
+    pub fn inverse_correlation_ref(&self) -> &Array2<T> {
+        self.rls.inverse_correlation_ref()
+    }
+
+    /// Returns a reference to the (tap) weight vector.
+    pub fn weight_ref(&self) -> &Array1<T> {
+        self.rls.weight_ref()
+    }
+
+    /// Returns a reference to the prior error.
+    pub fn prior_error_ref(&self) -> &T {
+        self.rls.prior_error_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::Lcg;
+
+    #[test]
+    fn unit_gain_constraint_holds_after_every_update() {
+        let mut rng = Lcg::new(67);
+        let constraint = Array2::from_elem((3, 1), 1.0);
+        let mut rls = ConstrainedRls::<f64>::new(0.1, 0.99, constraint, arr1(&[1.0])).unwrap();
+        assert!((rls.weight_ref().scalar_sum() - 1.0).abs() < 1e-12);
+
+        for _ in 0..200 {
+            let input = rng.array(3);
+            rls.update(&input, 2.0 * input[0] - 0.5 * input[1] - 0.5 * input[2]);
+            assert!((rls.weight_ref().scalar_sum() - 1.0).abs() < 1e-10);
+        }
+        // The true weights satisfy the constraint, so the filter converges to them.
+        assert!((rls.weight_ref() - &arr1(&[2.0, -0.5, -0.5])).iter().all(|d| d.abs() < 1e-2));
+    }
+
+    #[test]
+    fn rejects_invalid_constraints() {
+        let dependent = Array2::from_elem((3, 2), 1.0);
+        assert_eq!(ConstrainedRls::new(0.1, 0.99, dependent, arr1(&[1.0, 1.0])).unwrap_err(),
+            RlsError::SingularConstraint);
+
+        let constraint = Array2::from_elem((3, 1), 1.0);
+        assert_eq!(ConstrainedRls::new(0.1, 0.99, constraint, arr1(&[1.0, 1.0])).unwrap_err(),
+            RlsError::DimensionMismatch { expected: 1, got: 2 });
+    }
+}
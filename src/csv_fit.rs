@@ -0,0 +1,98 @@
+use ndarray::prelude::*;
+use csv::ReaderBuilder;
+
+use std::io::Read;
+
+use {Rls, RlsError};
+
+impl<F: NdFloat> Rls<F> {
+
+    /// Performs a recursive update for each record of the CSV data read from `reader`, returning
+    /// the prior errors of all records in order.
+    ///
+    /// Each record holds the input followed by the target, i.e. `n + 1` fields for a filter with
+    /// n inputs (not counting an [intercept](#method.with_bias)). If `has_header` is `true`, the
+    /// first record is skipped as a header.
+    ///
+    /// Returns [`RlsError::DimensionMismatch`] for a record with the wrong number of fields, and
+    /// [`RlsError::Csv`] for a record that cannot be read or contains a field that is not a
+    /// number. The filter keeps the updates of all records before the invalid one.
+    ///
+    /// [`RlsError::DimensionMismatch`]: enum.RlsError.html#variant.DimensionMismatch
+    /// [`RlsError::Csv`]: enum.RlsError.html#variant.Csv
+    pub fn fit_csv<R: Read>(&mut self, reader: R, has_header: bool) -> Result<Array1<F>, RlsError> {
+        let n = self.input_len();
+
+        let mut reader = ReaderBuilder::new()
+            .has_headers(has_header)
+            .flexible(true)
+            .from_reader(reader);
+
+        let mut input = Array1::zeros(n);
+        let mut prior_errors = Vec::new();
+        for record in reader.records() {
+            let record = record.map_err(|e| RlsError::Csv {
+                line: e.position().map_or(0, |position| position.line()),
+                message: e.to_string(),
+            })?;
+            if record.len() != n + 1 {
+                return Err(RlsError::DimensionMismatch {
+                    expected: n + 1,
+                    got: record.len(),
+                });
+            }
+
+            let line = record.position().map_or(0, |position| position.line());
+            let mut fields = record.iter().map(|field| {
+                field.trim().parse::<f64>()
+                    .map(|x| F::from(x).unwrap())
+                    .map_err(|e| RlsError::Csv {
+                        line,
+                        message: format!("invalid number `{}`: {}", field, e),
+                    })
+            });
+            for x in input.iter_mut() {
+                *x = fields.next().unwrap()?;
+            }
+            let target = fields.next().unwrap()?;
+
+            prior_errors.push(self.try_update(&input, target)?);
+        }
+        Ok(Array1::from_vec(prior_errors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_csv_updates_with_each_record() {
+        let data = "x0,x1,target\n1.0,0.0,2.0\n0.0,1.0,-1.0\n1.0,1.0,1.0\n";
+        let mut rls = Rls::<f64>::new(0.1, 1.0, 2);
+        let mut expected = rls.clone();
+        let prior_errors = rls.fit_csv(data.as_bytes(), true).unwrap();
+
+        let manual = arr1(&[
+            expected.update(&arr1(&[1.0, 0.0]), 2.0),
+            expected.update(&arr1(&[0.0, 1.0]), -1.0),
+            expected.update(&arr1(&[1.0, 1.0]), 1.0),
+        ]);
+        assert_eq!(prior_errors, manual);
+        assert_eq!(rls, expected);
+    }
+
+    #[test]
+    fn fit_csv_rejects_invalid_records() {
+        let mut rls = Rls::<f64>::new(0.1, 1.0, 2);
+        assert_eq!(rls.fit_csv("1.0,2.0\n".as_bytes(), false).unwrap_err(),
+            RlsError::DimensionMismatch { expected: 3, got: 2 });
+
+        match rls.fit_csv("1.0,2.0,3.0\n1.0,x,3.0\n".as_bytes(), false) {
+            Err(RlsError::Csv { line, .. }) => assert_eq!(line, 2),
+            result => panic!("unexpected result {:?}", result),
+        }
+        // The valid record before the invalid one was kept.
+        assert_eq!(rls.num_updates(), 1);
+    }
+}
@@ -0,0 +1,55 @@
+use ndarray::prelude::*;
+
+/// A tap-delay line of fixed length n, holding the last n samples of a time series with the
+/// most recent one first.
+///
+/// Pushing a sample shifts all previous samples down by one tap and discards the oldest, so that
+/// [`as_array`](#method.as_array) always returns the current tap input vector
+/// u(i) = (x(i), x(i-1), …, x(i-n+1)) for use with [`Rls`](struct.Rls.html).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug)]
+pub struct DelayLine<F> {
+
+    /// The samples in the delay line, the most recent one first.
+    taps: Array1<F>,
+}
+
+impl<F: NdFloat> DelayLine<F> {
+
+    /// Constructs a new DelayLine of length n filled with zeros.
+    pub fn new(n: usize) -> Self {
+        DelayLine {
+            taps: Array1::zeros(n),
+        }
+    }
+
+    /// Shifts the samples down by one tap, discarding the oldest, and inserts `x` at the front.
+    pub fn push(&mut self, x: F) {
+        let n = self.taps.len();
+        if n == 0 {
+            return;
+        }
+        for i in (1..n).rev() {
+            self.taps[i] = self.taps[i - 1];
+        }
+        self.taps[0] = x;
+    }
+}
+
+impl<T> DelayLine<T> {
+
+    /// Returns a view of the current tap input vector, the most recent sample first.
+    pub fn as_array(&self) -> ArrayView1<'_, T> {
+        self.taps.view()
+    }
+
+    /// Returns the number of taps.
+    pub fn len(&self) -> usize {
+        self.taps.len()
+    }
+
+    /// Returns `true` if the delay line has no taps.
+    pub fn is_empty(&self) -> bool {
+        self.taps.is_empty()
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(feature = "std")]
+use std::error::Error;
+
+use core::fmt;
+
+/// The errors that can occur when constructing or updating a recursive least squares filter.
+#[derive(Clone,Debug,PartialEq)]
+pub enum RlsError {
+    /// One of the internal buffers is not contiguous in memory and cannot be passed to BLAS.
+    NonContiguous,
+
+    /// The length of an input does not match the number of taps of the filter.
+    DimensionMismatch {
+        expected: usize,
+        got: usize,
+    },
+
+    /// The denominator of a rank-1 downdate of the inverse correlation matrix is not positive,
+    /// so removing the sample would make the matrix indefinite.
+    NonPositiveDenominator,
+
+    /// The forgetting factor λ does not satisfy 0 < λ ≤ 1.
+    InvalidForgettingFactor,
+
+    /// The initialization factor δ is not positive.
+    InvalidInitializationFactor,
+
+    /// The threshold δ_H of the Huber weight is not positive.
+    InvalidHuberDelta,
+
+    /// The minimum gain denominator is negative.
+    InvalidMinDenominator,
+
+    /// The smoothing factor α of the weights does not satisfy 0 < α ≤ 1.
+    InvalidSmoothingFactor,
+
+    /// The filter has no taps.
+    NoTaps,
+
+    /// The inverse correlation matrix is not symmetric.
+    NotSymmetric,
+
+    /// A required parameter was not set.
+    MissingParameter(&'static str),
+
+    /// An update produced a non-finite value, e.g. from a NaN or infinite input.
+    NonFinite,
+
+    /// Two filters with different forgetting factors cannot be combined.
+    ForgettingFactorMismatch,
+
+    /// Two filters of which only one has a bias term cannot be combined.
+    BiasMismatch,
+
+    /// The constraint matrix does not have full column rank.
+    SingularConstraint,
+
+    /// The correlation matrix of a batch of inputs is not positive definite.
+    SingularCorrelation,
+
+    /// A byte buffer does not hold a valid encoding of a filter.
+    InvalidBytes(&'static str),
+
+    /// The gain denominator of an update is smaller than the configured minimum, so the update
+    /// was skipped.
+    SmallDenominator,
+
+    /// A record of a CSV file could not be read or parsed.
+    #[cfg(feature = "csv")]
+    Csv {
+        line: u64,
+        message: String,
+    },
+}
+
+impl fmt::Display for RlsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RlsError::NonContiguous => write!(f, "buffer is not contiguous in memory"),
+            RlsError::DimensionMismatch { expected, got } => {
+                write!(f, "dimension mismatch: expected length {}, got {}", expected, got)
+            }
+            RlsError::NonPositiveDenominator => write!(f, "downdate denominator is not positive"),
+            RlsError::InvalidForgettingFactor => {
+                write!(f, "forgetting factor must satisfy 0 < λ <= 1")
+            }
+            RlsError::InvalidInitializationFactor => {
+                write!(f, "initialization factor must be positive")
+            }
+            RlsError::InvalidHuberDelta => write!(f, "Huber threshold must be positive"),
+            RlsError::InvalidMinDenominator => write!(f, "minimum denominator must be non-negative"),
+            RlsError::InvalidSmoothingFactor => write!(f, "smoothing factor must satisfy 0 < α <= 1"),
+            RlsError::NoTaps => write!(f, "filter must have at least one tap"),
+            RlsError::NotSymmetric => write!(f, "inverse correlation matrix is not symmetric"),
+            RlsError::MissingParameter(name) => write!(f, "missing parameter `{}`", name),
+            RlsError::NonFinite => write!(f, "update produced a non-finite value"),
+            RlsError::ForgettingFactorMismatch => write!(f, "forgetting factors do not match"),
+            RlsError::BiasMismatch => write!(f, "bias terms do not match"),
+            RlsError::SingularConstraint => write!(f, "constraint matrix does not have full column rank"),
+            RlsError::SingularCorrelation => write!(f, "correlation matrix is not positive definite"),
+            RlsError::InvalidBytes(reason) => write!(f, "invalid byte encoding: {}", reason),
+            RlsError::SmallDenominator => write!(f, "gain denominator is below the minimum"),
+            #[cfg(feature = "csv")]
+            RlsError::Csv { line, ref message } => write!(f, "invalid CSV record on line {}: {}", line, message),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl Error for RlsError {}
@@ -0,0 +1,150 @@
+use ndarray::prelude::*;
+
+use {ConstrainedRls, Rls, SlidingWindowRls, SqrtRls, StandardizedRls};
+
+/// The common interface of the adaptive filters operating on tap input vectors, e.g. to switch
+/// between them at runtime through a `Box<dyn AdaptiveFilter<f64>>`.
+///
+/// [`LatticeRls`](struct.LatticeRls.html) and [`FtfRls`](struct.FtfRls.html) do not implement
+/// it, since they take the newest sample of a time series instead of the tap input vector. The
+/// weights of a [`StandardizedRls`](struct.StandardizedRls.html) are those of the wrapped filter,
+/// including its intercept, with respect to the standardized inputs.
+pub trait AdaptiveFilter<F> {
+
+    /// Computes the filter output for `input` without updating the filter state.
+    fn predict(&self, input: ArrayView1<F>) -> F;
+
+    /// Updates the filter with `input` and the desired output `target`, returning the prior
+    /// error.
+    fn update(&mut self, input: ArrayView1<F>, target: F) -> F;
+
+    /// Returns a view of the current (tap) weight vector.
+    fn weights(&self) -> ArrayView1<'_, F>;
+}
+
+impl<F: NdFloat> AdaptiveFilter<F> for Rls<F> {
+    fn predict(&self, input: ArrayView1<F>) -> F {
+        Rls::predict(self, &input)
+    }
+
+    fn update(&mut self, input: ArrayView1<F>, target: F) -> F {
+        Rls::update(self, &input, target)
+    }
+
+    fn weights(&self) -> ArrayView1<'_, F> {
+        self.weight_ref().view()
+    }
+}
+
+impl<F: NdFloat> AdaptiveFilter<F> for SlidingWindowRls<F> {
+    fn predict(&self, input: ArrayView1<F>) -> F {
+        SlidingWindowRls::predict(self, &input)
+    }
+
+    fn update(&mut self, input: ArrayView1<F>, target: F) -> F {
+        SlidingWindowRls::update(self, &input, target)
+    }
+
+    fn weights(&self) -> ArrayView1<'_, F> {
+        self.weight_ref().view()
+    }
+}
+
+impl<F: NdFloat> AdaptiveFilter<F> for SqrtRls<F> {
+    fn predict(&self, input: ArrayView1<F>) -> F {
+        SqrtRls::predict(self, &input)
+    }
+
+    fn update(&mut self, input: ArrayView1<F>, target: F) -> F {
+        SqrtRls::update(self, &input, target)
+    }
+
+    fn weights(&self) -> ArrayView1<'_, F> {
+        self.weight_ref().view()
+    }
+}
+
+impl<F: NdFloat> AdaptiveFilter<F> for ConstrainedRls<F> {
+    fn predict(&self, input: ArrayView1<F>) -> F {
+        ConstrainedRls::predict(self, &input)
+    }
+
+    fn update(&mut self, input: ArrayView1<F>, target: F) -> F {
+        ConstrainedRls::update(self, &input, target)
+    }
+
+    fn weights(&self) -> ArrayView1<'_, F> {
+        self.weight_ref().view()
+    }
+}
+
+impl<F: NdFloat> AdaptiveFilter<F> for StandardizedRls<F> {
+    fn predict(&self, input: ArrayView1<F>) -> F {
+        StandardizedRls::predict(self, &input)
+    }
+
+    fn update(&mut self, input: ArrayView1<F>, target: F) -> F {
+        StandardizedRls::update(self, &input, target)
+    }
+
+    fn weights(&self) -> ArrayView1<'_, F> {
+        self.rls_ref().weight_ref().view()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::Lcg;
+
+    #[test]
+    fn trait_objects_behave_like_the_filters() {
+        let mut rng = Lcg::new(57);
+        let mut filters: Vec<Box<dyn AdaptiveFilter<f64>>> = vec![
+            Box::new(Rls::new(0.1, 1.0, 2)),
+            Box::new(SqrtRls::new(0.1, 1.0, 2)),
+            Box::new(SlidingWindowRls::new(0.1, 1.0, 2, 200)),
+        ];
+        let mut rls = Rls::new(0.1, 1.0, 2);
+
+        for _ in 0..100 {
+            let input = rng.array(2);
+            let target = 2.0 * input[0] - input[1];
+            let error = rls.update(&input, target);
+            for filter in &mut filters {
+                assert!((filter.update(input.view(), target) - error).abs() < 1e-8);
+            }
+        }
+
+        let input = arr1(&[0.3, -0.2]);
+        for filter in &filters {
+            assert!((filter.predict(input.view()) - rls.predict(&input)).abs() < 1e-8);
+            assert!((&filter.weights() - rls.weight_ref()).iter().all(|d| d.abs() < 1e-8));
+        }
+    }
+
+    #[test]
+    fn trait_objects_behave_like_the_wrappers() {
+        let mut rng = Lcg::new(57);
+        let constrained = ConstrainedRls::new(0.1, 1.0, arr2(&[[1.0], [1.0]]), arr1(&[1.0])).unwrap();
+        let standardized = StandardizedRls::new(0.1, 1.0, 2);
+        let mut boxed: Vec<Box<dyn AdaptiveFilter<f64>>> = vec![
+            Box::new(constrained.clone()),
+            Box::new(standardized.clone()),
+        ];
+        let (mut constrained, mut standardized) = (constrained, standardized);
+
+        for _ in 0..100 {
+            let input = rng.array(2);
+            let target = 2.0 * input[0] - input[1] + 0.01 * rng.next();
+            assert_eq!(boxed[0].update(input.view(), target), constrained.update(&input, target));
+            assert_eq!(boxed[1].update(input.view(), target), standardized.update(&input, target));
+        }
+
+        let input = arr1(&[0.3, -0.2]);
+        assert_eq!(boxed[0].predict(input.view()), constrained.predict(&input));
+        assert_eq!(boxed[1].predict(input.view()), standardized.predict(&input));
+        assert_eq!(boxed[0].weights(), constrained.weight_ref().view());
+        assert_eq!(boxed[1].weights(), standardized.rls_ref().weight_ref().view());
+    }
+}
@@ -0,0 +1,344 @@
+use ndarray::prelude::*;
+
+/// The weights of the direct computation in the mixtures of the two computations of the backward
+/// prediction error entering the update of the backward predictor and of its error energy, as
+/// recommended by Slock and Kailath.
+const PREDICTOR_MIXTURE: f64 = 1.5;
+const ENERGY_MIXTURE: f64 = 2.5;
+
+/// The parameters of a stabilized fast transversal filter (FTF) algorithm.
+///
+/// This solves the same problem as [`Rls`](struct.Rls.html) for a tap input vector
+/// u(i) = (x(i), x(i-1), …, x(i-n+1)) made of the last n samples of a time series, but in O(n)
+/// instead of O(n²) per update. Instead of the inverse correlation matrix, it propagates the
+/// forward and backward least squares predictors w_f and w_b of the time series with their
+/// error energies ζ_f and ζ_b, the a priori gain vector g(i) = λ^{-1} P(i-1) u(i), and the
+/// conversion factor γ(i) = 1 / (1 + u(i) · g(i)), from which the gain k(i) = γ(i) g(i) of the
+/// tap weights follows.
+///
+/// The plain FTF recursions are numerically unstable. Following Slock and Kailath, the backward
+/// prediction error is computed redundantly, both directly from the backward predictor and from
+/// the gain vector, and the difference of the two, which only stems from round-off, is fed back
+/// into the updates of the backward predictor and its error energy. This keeps the filter stable
+/// for forgetting factors close to 1, roughly λ ≥ 1 - 1 / (2n). If round-off nevertheless drives
+/// the conversion factor γ out of (0, 1] or an error energy to a non-positive value, the filter
+/// rescues itself by restarting the predictors, energies, and gain vector as after construction,
+/// as if the time series started anew, but keeping the tap weights, see
+/// [`num_rescues`](#method.num_rescues).
+///
+/// The filter is initialized with P = δ^{-1} · diag(1, λ, …, λ^{n-1}), which is the
+/// initialization compatible with the shift structure of the inputs and differs from the usual
+/// δ^{-1} · 𝟙 for λ < 1. Since the FTF requires the shift structure, `update` and `predict` take
+/// the newest sample x(i) instead of the whole tap input vector.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug)]
+pub struct FtfRls<F> {
+
+    /// The initialization factor δ, also used to rescue the filter.
+    initialization_factor: F,
+
+    /// The forgetting factor λ.
+    forgetting_factor: F,
+
+    /// The *(tap) weight vector* w(i).
+    weight: Array1<F>,
+
+    /// The forward predictor w_f(i), predicting x(i) from u(i-1).
+    forward_predictor: Array1<F>,
+
+    /// The backward predictor w_b(i), predicting x(i-n) from u(i).
+    backward_predictor: Array1<F>,
+
+    /// The minimum forward prediction error energy ζ_f(i).
+    forward_energy: F,
+
+    /// The minimum backward prediction error energy ζ_b(i).
+    backward_energy: F,
+
+    /// The a priori gain vector g(i) = λ^{-1} P(i-1) u(i).
+    gain: Array1<F>,
+
+    /// The conversion factor γ(i) = 1 / (1 + u(i) · g(i)).
+    conversion_factor: F,
+
+    /// The last n + 1 samples (x(i), x(i-1), …, x(i-n)), the most recent one first.
+    taps: Array1<F>,
+
+    /// The prior error, calculated as the difference between the desired output and the filter
+    /// output before an update.
+    prior_error: F,
+
+    /// The number of times the filter was rescued.
+    num_rescues: u64,
+}
+
+impl<F: NdFloat> FtfRls<F> {
+
+    /// Constructs a new FtfRls object with initialization factor δ and a weight vector of
+    /// length n.
+    pub fn new(initialization_factor: F, forgetting_factor: F, n: usize) -> Self {
+        let mut ftf = FtfRls {
+            initialization_factor,
+            forgetting_factor,
+            weight: Array1::zeros(n),
+            forward_predictor: Array1::zeros(n),
+            backward_predictor: Array1::zeros(n),
+            forward_energy: F::zero(),
+            backward_energy: F::zero(),
+            gain: Array1::zeros(n),
+            conversion_factor: F::one(),
+            taps: Array1::zeros(n + 1),
+            prior_error: F::zero(),
+            num_rescues: 0,
+        };
+        ftf.reset_predictors();
+        ftf
+    }
+
+    /// Reinitializes predictors, error energies, gain vector, and conversion factor, with
+    /// ζ_f = δ and ζ_b = δ λ^{-n} as for P = δ^{-1} · diag(1, λ, …, λ^{n-1}).
+    fn reset_predictors(&mut self) {
+        let n = self.weight.len();
+
+        self.forward_predictor.fill(F::zero());
+        self.backward_predictor.fill(F::zero());
+        self.gain.fill(F::zero());
+        self.conversion_factor = F::one();
+        self.forward_energy = self.initialization_factor;
+        self.backward_energy = self.initialization_factor / self.forgetting_factor.powi(n as i32);
+    }
+
+    /// Computes the filter output for the newest sample x(i) without updating the filter state.
+    pub fn predict(&self, sample: F) -> F {
+        let n = self.weight.len();
+        if n == 0 {
+            return F::zero();
+        }
+
+        let (last, rest) = self.weight.view().split_at(Axis(0), 1);
+        let previous = self.taps.view().split_at(Axis(0), n - 1).0;
+        last[0] * sample + rest.dot(&previous)
+    }
+
+    /// Performs a recursive update of the filter with the newest sample x(i) and the desired
+    /// output d(i), returning the prior error.
+    ///
+    /// The prior error equals that of the transversal filter, d(i) - w(i-1) · u(i), up to the
+    /// different initialization and round-off.
+    pub fn update(&mut self, sample: F, target: F) -> F {
+        let n = self.weight.len();
+        if n == 0 {
+            self.prior_error = target;
+            return self.prior_error;
+        }
+        let lambda = self.forgetting_factor;
+
+        // Forward prediction with the previous tap input vector u(i-1).
+        let forward_error = {
+            let previous = self.taps.view().split_at(Axis(0), n).0;
+            sample - self.forward_predictor.dot(&previous)
+        };
+
+        // Extend the gain to order n + 1 as (0, g(i-1)) + α(i) / (λ ζ_f(i-1)) (1, -w_f(i-1)).
+        let scale = forward_error / (lambda * self.forward_energy);
+        let mut extended_gain = Array1::zeros(n + 1);
+        extended_gain[0] = scale;
+        for j in 0..n {
+            extended_gain[j + 1] = self.gain[j] - scale * self.forward_predictor[j];
+        }
+
+        // The conversion factor of order n + 1, 1 / γ_{n+1}(i) = 1 / γ(i-1) + α(i)² / (λ ζ_f(i-1)).
+        let inv_extended_conversion_factor = F::one() / self.conversion_factor + scale * forward_error;
+
+        // Update the forward predictor with the gain k(i-1) = γ(i-1) g(i-1).
+        self.forward_energy = lambda * self.forward_energy
+            + self.conversion_factor * forward_error * forward_error;
+        self.forward_predictor.scaled_add(self.conversion_factor * forward_error, &self.gain);
+
+        // Shift the new sample into the taps, so that they hold (u(i), x(i-n)).
+        for j in (1..n + 1).rev() {
+            self.taps[j] = self.taps[j - 1];
+        }
+        self.taps[0] = sample;
+        let oldest = self.taps[n];
+
+        // The backward prediction error, computed directly and from the last element of the
+        // extended gain, which agree in exact arithmetic. Their difference is fed back into the
+        // updates of the backward predictor and its energy.
+        let backward_error = {
+            let input = self.taps.view().split_at(Axis(0), n).0;
+            oldest - self.backward_predictor.dot(&input)
+        };
+        let gain_backward_error = lambda * self.backward_energy * extended_gain[n];
+        let mix = |k: f64| {
+            let k = F::from(k).unwrap();
+            k * backward_error + (F::one() - k) * gain_backward_error
+        };
+        let predictor_backward_error = mix(PREDICTOR_MIXTURE);
+        let energy_backward_error = mix(ENERGY_MIXTURE);
+
+        // Reduce the gain to order n as g(i) = g_{n+1}(i)[..n] + w_b(i-1) β(i) / (λ ζ_b(i-1)),
+        // using the directly computed β(i).
+        let scale = backward_error / (lambda * self.backward_energy);
+        for j in 0..n {
+            self.gain[j] = extended_gain[j] + scale * self.backward_predictor[j];
+        }
+
+        // The conversion factor from its order recursion, 1 / γ(i) = 1 / γ_{n+1}(i) - β(i)² / (λ ζ_b(i-1)).
+        self.conversion_factor = F::one() / (inv_extended_conversion_factor - scale * backward_error);
+
+        // Update the backward predictor with the gain k(i) = γ(i) g(i).
+        self.backward_energy = lambda * self.backward_energy
+            + self.conversion_factor * energy_backward_error * energy_backward_error;
+        self.backward_predictor.scaled_add(self.conversion_factor * predictor_backward_error, &self.gain);
+
+        // Calculate the prior error using the not yet updated tap weight.
+        self.prior_error = {
+            let input = self.taps.view().split_at(Axis(0), n).0;
+            target - self.weight.dot(&input)
+        };
+
+        // Rescue the filter before its gain spoils the tap weight. Restarting the predictors
+        // assumes a prewindowed time series, so the past samples are cleared as well.
+        let healthy = self.conversion_factor > F::zero() && self.conversion_factor <= F::one()
+            && self.forward_energy > F::zero() && self.forward_energy.is_finite()
+            && self.backward_energy > F::zero() && self.backward_energy.is_finite()
+            && self.gain.iter().all(|g| g.is_finite());
+        if !healthy {
+            self.reset_predictors();
+            self.taps.fill(F::zero());
+            self.num_rescues += 1;
+            return self.prior_error;
+        }
+
+        self.weight.scaled_add(self.conversion_factor * self.prior_error, &self.gain);
+
+        self.prior_error
+    }
+}
+
+impl<T> FtfRls<T> {
+
+    /// Returns a reference to the forgetting factor λ.
+    pub fn forgetting_factor_ref(&self) -> &T {
+        &self.forgetting_factor
+    }
+
+    /// Returns a reference to the a priori gain vector g(i).
+    pub fn gain_ref(&self) -> &Array1<T> {
+        &self.gain
+    }
+
+    /// Returns a reference to the conversion factor γ(i).
+    pub fn conversion_factor_ref(&self) -> &T {
+        &self.conversion_factor
+    }
+
+    /// Returns a reference to the (tap) weight vector.
+    pub fn weight_ref(&self) -> &Array1<T> {
+        &self.weight
+    }
+
+    /// Returns a reference to the prior error.
+    pub fn prior_error_ref(&self) -> &T {
+        &self.prior_error
+    }
+
+    /// Returns the number of times the filter restarted its predictors after a numerical
+    /// breakdown.
+    pub fn num_rescues(&self) -> u64 {
+        self.num_rescues
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {DelayLine, Rls};
+    use test_util::Lcg;
+
+    /// Returns the next sample of an AR(1) time series.
+    fn next_sample(rng: &mut Lcg, previous: f64) -> f64 {
+        rng.next() + 0.9 * previous
+    }
+
+    #[test]
+    fn matches_rls_with_compatible_initialization() {
+        for &forgetting_factor in &[1.0f64, 0.99, 0.95] {
+            let n = 4;
+            let initialization_factor = 0.1;
+            let mut ftf = FtfRls::new(initialization_factor, forgetting_factor, n);
+            // Rls scales its inverse correlation matrix by λ^{-2} relative to the FTF initialization.
+            let inverse_correlation = Array2::from_shape_fn((n, n), |(i, j)| if i == j {
+                forgetting_factor.powi(i as i32 - 2) / initialization_factor
+            } else {
+                0.0
+            });
+            let mut rls = Rls::from_parts(1.0 / forgetting_factor, Array1::zeros(n), inverse_correlation)
+                .unwrap();
+            let mut delay_line = DelayLine::new(n);
+            let mut rng = Lcg::new(61);
+            let mut x = 0.0;
+
+            for _ in 0..300 {
+                x = next_sample(&mut rng, x);
+                delay_line.push(x);
+                let target = 0.5 * x - 0.3 * delay_line.as_array()[2] + 0.01 * rng.next();
+
+                assert!((ftf.predict(x) - rls.predict(&delay_line.as_array())).abs() < 1e-10);
+                let ftf_error = ftf.update(x, target);
+                let rls_error = rls.update(&delay_line.as_array(), target);
+                assert!((ftf_error - rls_error).abs() < 1e-10);
+            }
+            assert!((ftf.weight_ref() - rls.weight_ref()).iter().all(|d| d.abs() < 1e-10));
+            assert_eq!(ftf.num_rescues(), 0);
+        }
+    }
+
+    #[test]
+    fn stabilization_keeps_conversion_factor_consistent() {
+        // Within the stable range λ ≥ 1 - 1 / (2n), the conversion factor of the order recursion
+        // keeps agreeing with its direct computation 1 / (1 + u(i) · g(i)).
+        let n = 16;
+        let mut ftf = FtfRls::new(0.1, 0.98, n);
+        let mut rng = Lcg::new(61);
+        let mut x = 0.0;
+        for _ in 0..50000 {
+            let previous = x;
+            x = next_sample(&mut rng, x);
+            ftf.update(x, 0.3 * previous + 0.01 * rng.next());
+
+            let input = ftf.taps.view().split_at(Axis(0), n).0;
+            let direct = 1.0 / (1.0 + input.dot(&ftf.gain));
+            assert!(ftf.conversion_factor > 0.0 && ftf.conversion_factor <= 1.0);
+            assert!((ftf.conversion_factor - direct).abs() < 1e-8);
+        }
+        assert_eq!(ftf.num_rescues(), 0);
+    }
+
+    #[test]
+    fn rescues_itself_outside_stable_range() {
+        let n = 8;
+        let mut ftf = FtfRls::new(0.1, 0.9, n);
+        let mut rng = Lcg::new(61);
+        let mut x = 0.0;
+        for _ in 0..20000 {
+            let previous = x;
+            x = next_sample(&mut rng, x);
+            let rescues = ftf.num_rescues();
+            let error = ftf.update(x, 0.3 * previous + 0.01 * rng.next());
+            assert!(error.is_finite());
+
+            if ftf.num_rescues() > rescues {
+                assert_eq!(ftf.conversion_factor, 1.0);
+                assert!(ftf.gain.iter().all(|&g| g == 0.0));
+                assert!(ftf.taps.iter().all(|&x| x == 0.0));
+            }
+        }
+
+        assert!(ftf.num_rescues() > 0);
+        let mut expected = Array1::zeros(n);
+        expected[1] = 0.3;
+        assert!((ftf.weight_ref() - &expected).iter().all(|d| d.abs() < 1e-2));
+    }
+}
@@ -0,0 +1,73 @@
+use ndarray::prelude::*;
+use ndarray::Data;
+
+use Rls;
+
+/// An iterator over the a priori predictions of a filter, updating it with each sample, see
+/// [`Rls::predictions`](struct.Rls.html#method.predictions).
+pub struct PredictIter<'a, F: 'a, I> {
+    rls: &'a mut Rls<F>,
+    samples: I,
+}
+
+impl<'a, F, I> PredictIter<'a, F, I> {
+    pub(crate) fn new(rls: &'a mut Rls<F>, samples: I) -> Self {
+        PredictIter {
+            rls,
+            samples,
+        }
+    }
+}
+
+impl<'a, F, I, S> Iterator for PredictIter<'a, F, I>
+    where F: NdFloat,
+          I: Iterator<Item = (ArrayBase<S, Ix1>, F)>,
+          S: Data<Elem = F>,
+{
+    type Item = F;
+
+    /// Returns the filter output w(i-1) · u(i) for the next sample, before updating the filter
+    /// with it.
+    fn next(&mut self) -> Option<F> {
+        let (input, target) = self.samples.next()?;
+        let prediction = self.rls.predict(&input);
+        self.rls.update(&input, target);
+        Some(prediction)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.samples.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::Lcg;
+
+    #[test]
+    fn yields_prediction_before_each_update() {
+        let mut rng = Lcg::new(75);
+        let samples: Vec<_> = (0..50).map(|_| {
+            let input = rng.array(2);
+            let target = input[0] - input[1] + 0.1 * rng.next();
+            (input, target)
+        }).collect();
+
+        let mut rls = Rls::with_bias(0.01, 0.99, 2);
+        let mut expected = rls.clone();
+        for (prediction, (input, target)) in rls.predictions(samples.clone()).zip(&samples) {
+            assert_eq!(prediction, expected.predict(input));
+            expected.update(input, *target);
+        }
+        assert_eq!(rls.weight_ref(), expected.weight_ref());
+    }
+
+    #[test]
+    fn yields_finite_prediction_for_infinite_target() {
+        let mut rls = Rls::new(0.01, 0.99, 1);
+        rls.update(&arr1(&[1.0]), 2.0);
+        let prediction = rls.predictions(vec![(arr1(&[1.0]), f64::INFINITY)]).next().unwrap();
+        assert!(prediction.is_finite());
+    }
+}
@@ -0,0 +1,313 @@
+use ndarray::prelude::*;
+
+/// The parameters of a recursive least squares lattice (RLSL) algorithm.
+///
+/// This implements the a priori error-feedback form of the least squares lattice (cf. Haykin's
+/// Adaptive Filter Theory), which solves the same problem as [`Rls`](struct.Rls.html) for a tap
+/// input vector u(i) = (x(i), x(i-1), …, x(i-n+1)) made of the last n samples of a time series,
+/// but in O(n) instead of O(n²) per update. Instead of the tap weights, it propagates per stage
+/// the forward and backward reflection coefficients κ_f,m and κ_b,m of the prediction lattice,
+/// and the joint-process coefficients h_m weighting the backward prediction errors β_m(i).
+///
+/// Since the lattice requires the shift structure of the inputs, `update` and `predict` take
+/// the newest sample x(i) instead of the whole tap input vector.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug)]
+pub struct LatticeRls<F> {
+
+    /// The forgetting factor λ.
+    forgetting_factor: F,
+
+    /// The forward reflection coefficients κ_f,m of stages m = 1, …, n-1.
+    forward_reflection: Array1<F>,
+
+    /// The backward reflection coefficients κ_b,m of stages m = 1, …, n-1.
+    backward_reflection: Array1<F>,
+
+    /// The forward reflection coefficients of the n-2 updates before the last one, one update
+    /// per row, in a ring buffer whose oldest row is `history_start`.
+    past_forward_reflection: Array2<F>,
+
+    /// The backward reflection coefficients of the n-2 updates before the last one, stored like
+    /// `past_forward_reflection`.
+    past_backward_reflection: Array2<F>,
+
+    /// The row of the oldest reflection coefficients in the ring buffers.
+    history_start: usize,
+
+    /// The joint-process coefficients h_m of orders m = 0, …, n-1.
+    joint_process: Array1<F>,
+
+    /// The sums of weighted squared forward prediction errors F_m of orders m = 0, …, n-2.
+    forward_energy: Array1<F>,
+
+    /// The sums of weighted squared backward prediction errors B_m of orders m = 0, …, n-1.
+    backward_energy: Array1<F>,
+
+    /// The a priori backward prediction errors β_m of the last update.
+    backward_error: Array1<F>,
+
+    /// The conversion factors γ_m of the last update.
+    conversion_factor: Array1<F>,
+
+    /// The prior error of the joint-process estimation, calculated as the difference between the
+    /// desired output and the filter output before an update.
+    prior_error: F,
+}
+
+impl<F: NdFloat> LatticeRls<F> {
+
+    /// Constructs a new LatticeRls object with n taps and initialization factor δ, used as the
+    /// initial value of the prediction error energies.
+    pub fn new(initialization_factor: F, forgetting_factor: F, n: usize) -> Self {
+        let stages = n.saturating_sub(1);
+
+        LatticeRls {
+            forgetting_factor,
+            forward_reflection: Array1::zeros(stages),
+            backward_reflection: Array1::zeros(stages),
+            past_forward_reflection: Array2::zeros((n.saturating_sub(2), stages)),
+            past_backward_reflection: Array2::zeros((n.saturating_sub(2), stages)),
+            history_start: 0,
+            joint_process: Array1::zeros(n),
+            forward_energy: Array1::from_elem(stages, initialization_factor),
+            backward_energy: Array1::from_elem(n, initialization_factor),
+            backward_error: Array1::zeros(n),
+            conversion_factor: Array1::from_elem(n, F::one()),
+            prior_error: F::zero(),
+        }
+    }
+
+    /// Computes the filter output for the newest sample x(i) without updating the filter state.
+    pub fn predict(&self, sample: F) -> F {
+        let n = self.joint_process.len();
+        if n == 0 {
+            return F::zero();
+        }
+
+        let mut forward_error = sample;
+        let mut backward_error = sample;
+        let mut output = self.joint_process[0] * backward_error;
+        for m in 1..n {
+            let previous_backward_error = self.backward_error[m - 1];
+            backward_error = previous_backward_error + self.backward_reflection[m - 1] * forward_error;
+            forward_error += self.forward_reflection[m - 1] * previous_backward_error;
+            output += self.joint_process[m] * backward_error;
+        }
+        output
+    }
+
+    /// Performs a recursive update of the lattice with the newest sample x(i) and the desired
+    /// output d(i), returning the prior error.
+    ///
+    /// The prior error equals that of the transversal filter, d(i) - w(i-1) · u(i), up to the
+    /// different initialization.
+    pub fn update(&mut self, sample: F, target: F) -> F {
+        let n = self.joint_process.len();
+        if n == 0 {
+            self.prior_error = target;
+            return self.prior_error;
+        }
+        let lambda = self.forgetting_factor;
+
+        // Keep the reflection coefficients κ(i-1) for `transversal_weight`, overwriting the oldest.
+        let rows = self.past_forward_reflection.rows();
+        if rows > 0 {
+            let start = self.history_start;
+            self.past_forward_reflection.row_mut(start).assign(&self.forward_reflection);
+            self.past_backward_reflection.row_mut(start).assign(&self.backward_reflection);
+            self.history_start = (start + 1) % rows;
+        }
+
+        // The order-0 prediction errors are the sample itself.
+        let mut forward_error = sample;
+        let mut previous_backward_error = self.backward_error[0];
+        self.backward_error[0] = sample;
+
+        // Prediction stages, using the conversion factors γ_{m-1}(i-1) and energies
+        // B_{m-1}(i-1) of the last update.
+        for m in 1..n {
+            let gamma = self.conversion_factor[m - 1];
+
+            self.forward_energy[m - 1] = lambda * self.forward_energy[m - 1]
+                + gamma * forward_error * forward_error;
+
+            let next_forward_error = forward_error
+                + self.forward_reflection[m - 1] * previous_backward_error;
+            let next_backward_error = previous_backward_error
+                + self.backward_reflection[m - 1] * forward_error;
+
+            self.forward_reflection[m - 1] -= gamma * previous_backward_error * next_forward_error
+                / self.backward_energy[m - 1];
+            self.backward_reflection[m - 1] -= gamma * forward_error * next_backward_error
+                / self.forward_energy[m - 1];
+
+            previous_backward_error = self.backward_error[m];
+            self.backward_error[m] = next_backward_error;
+            forward_error = next_forward_error;
+        }
+
+        // Backward error energies B_m(i) and conversion factors γ_m(i) of the current update.
+        let mut gamma = F::one();
+        for m in 0..n {
+            let beta = self.backward_error[m];
+            self.backward_energy[m] = lambda * self.backward_energy[m] + gamma * beta * beta;
+            self.conversion_factor[m] = gamma;
+            gamma -= gamma * gamma * beta * beta / self.backward_energy[m];
+        }
+
+        // Joint-process estimation, order by order.
+        let mut error = target;
+        for m in 0..n {
+            let beta = self.backward_error[m];
+            let next_error = error - self.joint_process[m] * beta;
+            self.joint_process[m] += self.conversion_factor[m] * beta * next_error
+                / self.backward_energy[m];
+            error = next_error;
+        }
+
+        self.prior_error = error;
+        self.prior_error
+    }
+
+    /// Returns the equivalent transversal (tap) weight vector w(i) with y(i+1) = w(i) · u(i+1).
+    ///
+    /// The prior backward prediction error β_m(i+1) applies the backward predictor c_m(i) to the
+    /// tap inputs, which the order recursion of the least squares predictors builds from the
+    /// reflection coefficients κ_m(i) and the backward predictor c_{m-1}(i-1) of the previous
+    /// update. The conversion therefore uses the reflection coefficients of the last n-1 updates
+    /// and costs O(n³).
+    pub fn transversal_weight(&self) -> Array1<F> {
+        let n = self.joint_process.len();
+        let mut weight = Array1::zeros(n);
+        if n == 0 {
+            return weight;
+        }
+
+        // Forward predictors a_m and backward predictors c_m of order m after the update `lag`
+        // updates ago in row `lag`, with the coefficient of x(i - k) at index k.
+        let mut forward = Array2::zeros((n, n));
+        let mut backward = Array2::zeros((n, n));
+        forward.column_mut(0).fill(F::one());
+        backward.column_mut(0).fill(F::one());
+        weight.scaled_add(self.joint_process[0], &backward.row(0));
+
+        // Raising the order by one needs the order m-1 predictors one update further back, so
+        // each order is known for one update fewer.
+        for m in 1..n {
+            let mut next_forward = Array2::zeros((n - m, n));
+            let mut next_backward = Array2::zeros((n - m, n));
+            for lag in 0..n - m {
+                let (forward_reflection, backward_reflection) = self.reflection(m - 1, lag);
+                for k in 0..m + 1 {
+                    let shifted_backward = if k > 0 { backward[[lag + 1, k - 1]] } else { F::zero() };
+                    next_forward[[lag, k]] = forward[[lag, k]]
+                        + forward_reflection * shifted_backward;
+                    next_backward[[lag, k]] = shifted_backward
+                        + backward_reflection * forward[[lag, k]];
+                }
+            }
+
+            forward = next_forward;
+            backward = next_backward;
+            weight.scaled_add(self.joint_process[m], &backward.row(0));
+        }
+        weight
+    }
+
+    /// Returns the forward and backward reflection coefficients of `stage` after the update `lag`
+    /// updates ago.
+    fn reflection(&self, stage: usize, lag: usize) -> (F, F) {
+        if lag == 0 {
+            return (self.forward_reflection[stage], self.backward_reflection[stage]);
+        }
+        let rows = self.past_forward_reflection.rows();
+        let row = (self.history_start + rows - lag) % rows;
+        (self.past_forward_reflection[[row, stage]], self.past_backward_reflection[[row, stage]])
+    }
+}
+
+impl<T> LatticeRls<T> {
+
+    /// Returns a reference to the forgetting factor λ.
+    pub fn forgetting_factor_ref(&self) -> &T {
+        &self.forgetting_factor
+    }
+
+    /// Returns a reference to the forward reflection coefficients.
+    pub fn forward_reflection_ref(&self) -> &Array1<T> {
+        &self.forward_reflection
+    }
+
+    /// Returns a reference to the backward reflection coefficients.
+    pub fn backward_reflection_ref(&self) -> &Array1<T> {
+        &self.backward_reflection
+    }
+
+    /// Returns a reference to the joint-process coefficients.
+    pub fn joint_process_ref(&self) -> &Array1<T> {
+        &self.joint_process
+    }
+
+    /// Returns a reference to the prior error.
+    pub fn prior_error_ref(&self) -> &T {
+        &self.prior_error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use {DelayLine, Rls};
+    use test_util::Lcg;
+
+    /// Runs a 4-tap lattice and transversal filter on an AR(2) input and returns the largest
+    /// differences of their prior errors and weights after the first 100 updates.
+    fn compare_with_rls(forgetting_factor: f64) -> (f64, f64) {
+        let n = 4;
+        let channel = arr1(&[0.5, -0.3, 0.2, 0.1]);
+        let mut rls = Rls::new(1e-2, forgetting_factor, n);
+        let mut lattice = LatticeRls::new(1e-2, forgetting_factor, n);
+        let mut delay_line = DelayLine::new(n);
+        let mut rng = Lcg::new(43);
+        let (mut x1, mut x2) = (0.0, 0.0);
+
+        let mut max_error_difference: f64 = 0.0;
+        let mut max_weight_difference: f64 = 0.0;
+        for i in 0..2000 {
+            let x = 1.2 * x1 - 0.5 * x2 + rng.next();
+            x2 = x1;
+            x1 = x;
+            delay_line.push(x);
+            let input = delay_line.as_array().to_owned();
+            let target = channel.dot(&input) + 0.1 * rng.next();
+
+            let prediction = lattice.predict(x);
+            let rls_error = rls.update(&input, target);
+            let lattice_error = lattice.update(x, target);
+            assert!((target - prediction - lattice_error).abs() < 1e-12);
+
+            if i >= 100 {
+                let weight_difference = (&lattice.transversal_weight() - rls.weight_ref())
+                    .fold(0.0, |max: f64, &d| max.max(d.abs()));
+                max_error_difference = max_error_difference.max((rls_error - lattice_error).abs());
+                max_weight_difference = max_weight_difference.max(weight_difference);
+            }
+        }
+        (max_error_difference, max_weight_difference)
+    }
+
+    #[test]
+    fn matches_rls_without_forgetting() {
+        let (error_difference, weight_difference) = compare_with_rls(1.0);
+        assert!(error_difference < 1e-12);
+        assert!(weight_difference < 1e-12);
+    }
+
+    #[test]
+    fn matches_rls_with_forgetting() {
+        let (error_difference, weight_difference) = compare_with_rls(0.99);
+        assert!(error_difference < 1e-4);
+        assert!(weight_difference < 1e-4);
+    }
+}
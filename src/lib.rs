@@ -1,6 +1,32 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+#[cfg(not(any(feature = "std", test)))]
+extern crate alloc;
+#[cfg(any(feature = "std", test))]
+extern crate core;
+
+#[cfg(feature = "blas")]
 extern crate blas;
 
 extern crate ndarray;
+extern crate num_complex;
+
+#[cfg(feature = "bincode")]
+extern crate bincode;
+
+#[cfg(feature = "csv")]
+extern crate csv;
+
+#[cfg(feature = "npy")]
+extern crate ndarray_npy;
+#[cfg(feature = "npy")]
+extern crate npy_ndarray;
+
+#[cfg(feature = "rand")]
+extern crate rand;
+
+#[cfg(feature = "rayon")]
+extern crate rayon;
 
 #[cfg(feature = "serde")]
 extern crate serde;
@@ -9,12 +35,73 @@ extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 
+mod builder;
+mod bytes;
+mod complex;
+mod constrained;
+#[cfg(feature = "csv")]
+mod csv_fit;
+mod delay_line;
+mod error;
+mod filter;
+mod ftf;
+mod iter;
+mod lattice;
+mod mimo;
+#[cfg(feature = "npy")]
+mod npy;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "bincode")]
+mod persistence;
+#[cfg(feature = "rand")]
+mod posterior;
+mod sliding_window;
+mod sqrt;
+mod standardize;
+#[cfg(test)]
+mod test_util;
+
+pub use builder::RlsBuilder;
+pub use complex::ComplexRls;
+pub use constrained::ConstrainedRls;
+pub use delay_line::DelayLine;
+pub use error::RlsError;
+pub use filter::AdaptiveFilter;
+pub use ftf::FtfRls;
+pub use iter::PredictIter;
+pub use lattice::LatticeRls;
+pub use mimo::{BankRls, MimoRls};
+#[cfg(feature = "rayon")]
+pub use parallel::par_update_bank;
+pub use sliding_window::SlidingWindowRls;
+pub use sqrt::SqrtRls;
+pub use standardize::{Standardizer, StandardizedRls};
+
 use ndarray::prelude::*;
-use ndarray::Data;
+use ndarray::{Data, DataMut};
 use ndarray::linalg::{
     general_mat_vec_mul,
 };
 
+#[cfg(not(any(feature = "std", test)))]
+use alloc::vec::Vec;
+
+use core::fmt;
+use core::mem;
+use core::ops::Range;
+
+#[cfg(feature = "blas")]
+use core::any::TypeId;
+#[cfg(feature = "blas")]
+use core::slice;
+
+/// The initialization factor δ used by [`Rls::with_defaults`](struct.Rls.html#method.with_defaults).
+pub const DEFAULT_INITIALIZATION_FACTOR: f64 = 0.01;
+
+/// The forgetting factor λ used by [`Rls::with_defaults`](struct.Rls.html#method.with_defaults).
+pub const DEFAULT_FORGETTING_FACTOR: f64 = 0.99;
+
 /// The parameters of recursive least squares algorithm.
 ///
 /// This struct contains all parameters involved in a recursive
@@ -22,14 +109,24 @@ use ndarray::linalg::{
 /// [Haykin's Adaptive Filter Theory][http://www.isbnsearch.org/isbn/9780132671453].
 /// The implementation here does not implicitly take time into account. By making a choice of the
 /// forgetting factor λ < 1 and shifting down old values of the input vector manually, the user can
-/// get this algorithm to behave accordingly.
+/// get this algorithm to behave accordingly. [`DelayLine`](struct.DelayLine.html) does the latter.
+///
+/// Since the filter owns all of its state, `Rls<F>` is `Send` and `Sync` whenever `F` is, so that
+/// it can be moved to another thread or shared behind an `Arc<Mutex<_>>`.
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Clone,Debug)]
 pub struct Rls<F> {
 
+    /// The initialization factor δ the inverse correlation matrix was last initialized with.
+    initialization_factor: F,
+
     /// The inverse forgetting factor λ^{-1}.
     inv_forgetting_factor: F,
 
+    /// The inverse forgetting factor of the rescaling of the inverse correlation matrix, if it
+    /// differs from the one of the gain, see `Rls::with_dual_forgetting`.
+    inv_covariance_forgetting_factor: Option<F>,
+
     /// The gain vector used during the update of the inverse correlation matrix P(i) and the
     /// (tap) weight vector w(i).
     gain: Array1<F>,
@@ -48,132 +145,4335 @@ pub struct Rls<F> {
     /// desired output and the filter output before an update.
     prior_error: F,
 
-    // Two scratch matrices used for for update of the inverse correlation matrix.
-    temp_mat: Array2<F>,
+    // Scratch vector used for the update of the inverse correlation matrix. It is not serialized
+    // and reallocated by the first update after deserialization.
+    #[cfg_attr(feature = "serde", serde(skip))]
     temp_vec: Array1<F>,
+
+    /// The policy adapting the forgetting factor to the prior error, if any.
+    vff_policy: Option<VffPolicy<F>>,
+
+    /// The schedule setting the forgetting factor from the number of updates, if any.
+    forgetting_schedule: Option<ForgettingSchedule<F>>,
+
+    /// The leakage γ shrinking the weight vector towards zero in each update as
+    /// w(i) = (1 - λ γ) w(i-1) + k(i) e(i).
+    leakage: F,
+
+    /// The exponentially weighted sum Σ λ^{i-k} e_post(k)² of squared posterior errors.
+    residual_energy: F,
+
+    /// The exponentially weighted number of samples Σ λ^{i-k} entering `residual_energy`.
+    residual_weight: F,
+
+    /// The exponentially weighted sum Σ λ^{i-k} d(k) of the targets.
+    target_sum: F,
+
+    /// The exponentially weighted sum Σ λ^{i-k} d(k)² of squared targets.
+    target_energy: F,
+
+    /// The number of updates after which the inverse correlation matrix is symmetrized, or 0 to
+    /// never symmetrize it automatically.
+    symmetrize_every: usize,
+
+    /// The number of updates since the inverse correlation matrix was last symmetrized.
+    updates_since_symmetrize: usize,
+
+    /// The trigger resetting the inverse correlation matrix once its trace becomes too small,
+    /// if any.
+    covariance_reset: Option<CovarianceReset<F>>,
+
+    /// Whether updates only compute the prior error and leave the filter unchanged.
+    frozen: bool,
+
+    /// The smallest magnitude of the gain denominator λ^{-1} + u(i) · P(i-1) u(i) an update is
+    /// performed with, or 0 to never skip an update.
+    min_denominator: F,
+
+    /// The norm |e(i) k(i)| of the most recent weight correction.
+    weight_delta_norm: F,
+
+    /// The Tikhonov regularization μ kept in the correlation matrix as μ · 𝟙.
+    regularization: F,
+
+    /// The tap the next regularization step is applied to.
+    regularization_tap: usize,
+
+    /// The number of updates applied since construction or the last reset.
+    num_updates: u64,
+
+    /// Whether the first tap is an intercept, fed a constant 1 in front of each input.
+    bias: bool,
+
+    // Scratch vector holding the input with the constant 1 prepended. It is not serialized and
+    // reallocated by the first update after deserialization.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    bias_input: Array1<F>,
+
+    /// The last prior errors for the windowed RMSE, if enabled.
+    error_window: Option<ErrorWindow<F>>,
+
+    /// The weight vectors after each update, if recorded.
+    weight_history: Option<Vec<Array1<F>>>,
+
+    /// The exponentially smoothed weight vector, if enabled.
+    weight_smoothing: Option<WeightSmoothing<F>>,
+
+    /// The running means and variances of the inputs, including the constant 1 of an intercept.
+    input_statistics: Standardizer<F>,
+}
+
+/// A summary of a single update, see `Rls::update_reporting`.
+#[derive(Clone,Copy,Debug,PartialEq)]
+pub struct StepReport<F> {
+    /// The prior error e(i) = d(i) - w(i-1) · u(i).
+    pub prior_error: F,
+
+    /// The Euclidean norm |k(i)| of the gain vector.
+    pub gain_norm: F,
+
+    /// The Euclidean norm |e(i) k(i)| of the weight correction.
+    pub weight_delta_norm: F,
+
+    /// The trace of the updated inverse correlation matrix P(i).
+    pub covariance_trace: F,
+}
+
+/// The part of the filter state mutated by an update, see `Rls::snapshot`.
+#[derive(Clone,Debug)]
+pub struct RlsSnapshot<F> {
+    weight: Array1<F>,
+    gain: Array1<F>,
+    inverse_correlation: Array2<F>,
+    prior_error: F,
+    inv_forgetting_factor: F,
+    residual_energy: F,
+    residual_weight: F,
+    target_sum: F,
+    target_energy: F,
+    updates_since_symmetrize: usize,
+    weight_delta_norm: F,
+    regularization_tap: usize,
+    num_updates: u64,
+    error_window: Option<ErrorWindow<F>>,
+    history_len: usize,
+    weight_smoothing: Option<WeightSmoothing<F>>,
+    input_statistics: Standardizer<F>,
+}
+
+/// A policy for a variable forgetting factor driven by the prior error, see
+/// `Rls::set_vff_policy`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Copy,Debug,PartialEq)]
+struct VffPolicy<F> {
+    min_forgetting_factor: F,
+    max_forgetting_factor: F,
+    sensitivity: F,
+}
+
+/// A schedule of the forgetting factor over the number of updates, see
+/// `Rls::set_forgetting_schedule`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Copy,Debug,PartialEq)]
+struct ForgettingSchedule<F> {
+    initial_forgetting_factor: F,
+    final_forgetting_factor: F,
+    rate: F,
+}
+
+/// A trigger for resetting the inverse correlation matrix, see
+/// `Rls::reset_covariance_when_trace_below`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Copy,Debug,PartialEq)]
+struct CovarianceReset<F> {
+    threshold: F,
+    initialization_factor: F,
+}
+
+/// An exponential moving average of the weight vector, see `RlsBuilder::weight_smoothing`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug,PartialEq)]
+struct WeightSmoothing<F> {
+    /// The smoothing factor α of the newest weight vector.
+    alpha: F,
+
+    /// The smoothed weight vector.
+    weight: Array1<F>,
+}
+
+/// A ring buffer of the last prior errors, see `RlsBuilder::error_window`.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug,PartialEq)]
+struct ErrorWindow<F> {
+    /// The stored prior errors, of length K.
+    errors: Array1<F>,
+
+    /// The index the next prior error is written to.
+    next: usize,
+
+    /// The number of stored prior errors, at most K.
+    len: usize,
+}
+
+impl<F: NdFloat> ErrorWindow<F> {
+    fn new(k: usize) -> Self {
+        ErrorWindow {
+            errors: Array1::zeros(k),
+            next: 0,
+            len: 0,
+        }
+    }
+
+    /// Stores `error`, overwriting the oldest one once the window is full.
+    fn push(&mut self, error: F) {
+        let k = self.errors.len();
+        self.errors[self.next] = error;
+        self.next = (self.next + 1) % k;
+        self.len = k.min(self.len + 1);
+    }
+
+    fn clear(&mut self) {
+        self.errors.fill(F::zero());
+        self.next = 0;
+        self.len = 0;
+    }
+
+    /// Returns the root mean square of the stored errors, or zero if there are none.
+    fn rmse(&self) -> F {
+        if self.len == 0 {
+            return F::zero();
+        }
+        // Unused slots of a window that is not yet full are zero.
+        let sum = self.errors.iter().fold(F::zero(), |sum, &e| sum + e * e);
+        (sum / F::from(self.len).unwrap()).sqrt()
+    }
+}
+
+/// How a sample enters the update.
+#[derive(Clone,Copy,Debug)]
+enum UpdateKind<F> {
+    /// The standard update with exponential forgetting.
+    Standard,
+    /// Downweighting by the Huber weight with the given threshold.
+    Huber(F),
+    /// Forgetting only along the input.
+    Directional,
+    /// Correcting the weights by the sign of the prior error.
+    SignError,
+    /// Weighting the sample with the given observation weight.
+    Weighted(F),
+}
+
+impl<F: NdFloat> ForgettingSchedule<F> {
+    /// Returns the forgetting factor of the update following `num_updates` updates.
+    fn forgetting_factor(&self, num_updates: u64) -> F {
+        let decay = (-self.rate * F::from(num_updates).unwrap()).exp();
+        self.final_forgetting_factor - (self.final_forgetting_factor - self.initial_forgetting_factor) * decay
+    }
+}
+
+impl<F: NdFloat> VffPolicy<F> {
+    /// Returns the forgetting factor for the normalized squared prior error ε.
+    fn forgetting_factor(&self, normalized_squared_error: F) -> F {
+        let range = self.max_forgetting_factor - self.min_forgetting_factor;
+        self.max_forgetting_factor - range * (F::one() - (-self.sensitivity * normalized_squared_error).exp())
+    }
 }
 
 impl<F: NdFloat> Rls<F> {
 
+    /// Returns a builder for validated construction of an Rls object.
+    pub fn builder() -> RlsBuilder<F> {
+        RlsBuilder::new()
+    }
+
     /// Constructs a new Rls object with initialization factor δ and a weight vector of length n.
+    ///
+    /// The parameters are not validated, see [`try_new`](#method.try_new).
     pub fn new(initialization_factor: F, forgetting_factor: F, n: usize) -> Self {
         let weight = Array1::zeros(n);
 
         Rls::with_weight(initialization_factor, forgetting_factor, weight)
     }
 
+    /// Constructs a new Rls object with initialization factor δ and a weight vector of length n,
+    /// validating the parameters.
+    ///
+    /// Returns an error if λ ≤ 0 or λ > 1, if δ ≤ 0, or if n = 0.
+    pub fn try_new(initialization_factor: F, forgetting_factor: F, n: usize) -> Result<Self, RlsError> {
+        Rls::try_with_weight(initialization_factor, forgetting_factor, Array1::zeros(n))
+    }
+
+    /// Constructs a new Rls object with a weight vector of length n, using the initialization
+    /// factor δ = [`DEFAULT_INITIALIZATION_FACTOR`] and the forgetting factor
+    /// λ = [`DEFAULT_FORGETTING_FACTOR`].
+    ///
+    /// These are generic starting points from Haykin's Adaptive Filter Theory for data of roughly
+    /// unit variance: δ should be scaled with the variance of the input, and λ lowered for faster
+    /// tracking of changing systems.
+    ///
+    /// [`DEFAULT_INITIALIZATION_FACTOR`]: constant.DEFAULT_INITIALIZATION_FACTOR.html
+    /// [`DEFAULT_FORGETTING_FACTOR`]: constant.DEFAULT_FORGETTING_FACTOR.html
+    pub fn with_defaults(n: usize) -> Self {
+        Rls::new(
+            F::from(DEFAULT_INITIALIZATION_FACTOR).unwrap(),
+            F::from(DEFAULT_FORGETTING_FACTOR).unwrap(),
+            n,
+        )
+    }
+
+    /// Constructs a new Rls object with initialization factor δ and pre-defined weight w, validating
+    /// the parameters.
+    ///
+    /// Returns an error if λ ≤ 0 or λ > 1, if δ ≤ 0, or if w is empty.
+    pub fn try_with_weight(initialization_factor: F, forgetting_factor: F, weight: Array1<F>)
+        -> Result<Self, RlsError>
+    {
+        if forgetting_factor.is_nan() || forgetting_factor <= F::zero() || forgetting_factor > F::one() {
+            return Err(RlsError::InvalidForgettingFactor);
+        }
+        if initialization_factor.is_nan() || initialization_factor <= F::zero() {
+            return Err(RlsError::InvalidInitializationFactor);
+        }
+        if weight.is_empty() {
+            return Err(RlsError::NoTaps);
+        }
+
+        Ok(Rls::with_weight(initialization_factor, forgetting_factor, weight))
+    }
+
     /// Constructs a new Rls object with initialization factor δ and pre-defined weight w.
+    ///
+    /// The parameters are not validated, see [`try_with_weight`](#method.try_with_weight).
     pub fn with_weight(initialization_factor: F, forgetting_factor: F, weight: Array1<F>) -> Self {
         let one = F::one();
-        let zero = F::zero();
 
         let n = weight.len();
 
         let inv_forgetting_factor = one / forgetting_factor;
 
-        let gain = Array1::zeros(n);
-        let prior_error = zero;
-
         let mut inverse_correlation = Array2::eye(n);
         inverse_correlation *= one/initialization_factor;
 
-        let temp_mat = Array2::zeros([n,n]);
+        Rls::from_parts_unchecked(initialization_factor, inv_forgetting_factor, weight, inverse_correlation)
+    }
+
+    /// Constructs a new Rls object with initialization factor δ and pre-defined weight w, copied
+    /// from a slice.
+    pub fn from_slice(initialization_factor: F, forgetting_factor: F, weights: &[F]) -> Self {
+        Rls::with_weight(initialization_factor, forgetting_factor, Array1::from_vec(weights.to_vec()))
+    }
+
+    /// Constructs a new Rls object from the inverse forgetting factor λ^{-1}, the weight vector
+    /// w, and the inverse correlation matrix P, e.g. as previously returned by
+    /// [`into_parts`](#method.into_parts).
+    ///
+    /// Since P does not determine an initialization factor, the filter uses
+    /// [`DEFAULT_INITIALIZATION_FACTOR`](constant.DEFAULT_INITIALIZATION_FACTOR.html) for
+    /// [`clone_reset`](#method.clone_reset).
+    ///
+    /// Returns an error if λ^{-1} is not finite or less than 1, i.e. unless 0 < λ ≤ 1, if P is
+    /// not square, if the length of w does not match P, or if P is not symmetric up to
+    /// round-off.
+    pub fn from_parts(inv_forgetting_factor: F, weight: Array1<F>, inverse_correlation: Array2<F>)
+        -> Result<Self, RlsError>
+    {
+        if !inv_forgetting_factor.is_finite() || inv_forgetting_factor < F::one() {
+            return Err(RlsError::InvalidForgettingFactor);
+        }
+        let (rows, cols) = inverse_correlation.dim();
+        if rows != cols {
+            return Err(RlsError::DimensionMismatch {
+                expected: rows,
+                got: cols,
+            });
+        }
+        if weight.len() != rows {
+            return Err(RlsError::DimensionMismatch {
+                expected: rows,
+                got: weight.len(),
+            });
+        }
+
+        let scale = inverse_correlation.fold(F::zero(), |max, &p| max.max(p.abs()));
+        let tolerance = F::epsilon().sqrt() * scale;
+        let symmetric = inverse_correlation.indexed_iter()
+            .all(|((i, j), &p)| (p - inverse_correlation[[j, i]]).abs() <= tolerance);
+        if !symmetric {
+            return Err(RlsError::NotSymmetric);
+        }
+
+        let initialization_factor = F::from(DEFAULT_INITIALIZATION_FACTOR).unwrap();
+
+        Ok(Rls::from_parts_unchecked(initialization_factor, inv_forgetting_factor, weight, inverse_correlation))
+    }
+
+    fn from_parts_unchecked(
+        initialization_factor: F,
+        inv_forgetting_factor: F,
+        weight: Array1<F>,
+        inverse_correlation: Array2<F>,
+    ) -> Self
+    {
+        let zero = F::zero();
+
+        let n = weight.len();
+
+        let gain = Array1::zeros(n);
+        let prior_error = zero;
+
         let temp_vec = Array1::zeros(n);
 
         Rls {
+            initialization_factor,
             inv_forgetting_factor,
+            inv_covariance_forgetting_factor: None,
             gain,
             inverse_correlation,
             weight,
             prior_error,
-            temp_mat,
             temp_vec,
+            vff_policy: None,
+            forgetting_schedule: None,
+            leakage: zero,
+            residual_energy: zero,
+            residual_weight: zero,
+            target_sum: zero,
+            target_energy: zero,
+            symmetrize_every: 0,
+            updates_since_symmetrize: 0,
+            covariance_reset: None,
+            frozen: false,
+            min_denominator: zero,
+            weight_delta_norm: zero,
+            regularization: zero,
+            regularization_tap: 0,
+            num_updates: 0,
+            bias: false,
+            bias_input: Array1::zeros(0),
+            error_window: None,
+            weight_history: None,
+            weight_smoothing: None,
+            input_statistics: Standardizer::new(n),
         }
     }
-}
 
-macro_rules! impl_update {
-    ($t:ty, $fn:expr) => {
-        impl Rls<$t> {
-            /// Performs a recursive update of inverse correlation matrix and weight vector.
-            pub fn update<S>(&mut self, input: &ArrayBase<S, Ix1>, target: $t)
-                where S: Data<Elem = $t>
-            {
-                // Update the gain vector.
-                general_mat_vec_mul(
-                    1.0,
-                    &self.inverse_correlation,
-                    input,
-                    0.0,
-                    &mut self.gain
-                );
-                let c = self.inv_forgetting_factor + input.dot(&self.gain);
+    /// Decomposes the Rls object into the inverse forgetting factor λ^{-1}, the weight vector w,
+    /// and the inverse correlation matrix P.
+    pub fn into_parts(self) -> (F, Array1<F>, Array2<F>) {
+        (self.inv_forgetting_factor, self.weight, self.inverse_correlation)
+    }
 
-                self.gain /= c;
+    /// Returns a copy of the weight vector as a `Vec`.
+    pub fn weights_to_vec(&self) -> Vec<F> {
+        self.weight.to_vec()
+    }
 
-                // Calculate the prior error using the not yet updated tap weight.
-                self.prior_error = target - self.weight.dot(&input);
+    /// Returns the Euclidean norm |w| of the weight vector.
+    pub fn weights_l2_norm(&self) -> F {
+        self.weight.dot(&self.weight).sqrt()
+    }
 
-                // Update the tap weight.
-                self.weight.scaled_add(self.prior_error, &self.gain);
+    /// Divides the weight vector by its Euclidean norm, e.g. for the read-out of beamforming
+    /// weights, leaving a zero weight vector unchanged.
+    ///
+    /// *Note:* The normalized weights are no longer the least squares solution belonging to
+    /// the inverse correlation matrix, so this is meant for deployment, not for continued
+    /// training.
+    pub fn normalize_weights(&mut self) {
+        let norm = self.weights_l2_norm();
+        if norm > F::zero() {
+            self.weight /= norm;
+        }
+    }
 
-                general_mat_vec_mul(
-                    1.0,
-                    &self.inverse_correlation.t(),
-                    input,
-                    0.0,
-                    &mut self.temp_vec
-                );
+    /// Returns the heuristic importance w_j² σ_j² of each tap j, normalized to sum to 1, with the
+    /// variance σ_j² of the tap inputs over all updates since construction or the last
+    /// [`reset`](#method.reset). An [intercept](#method.with_bias) is omitted.
+    ///
+    /// This is the share of the variance of the output due to each tap if the taps were
+    /// uncorrelated, a cheap sense of which taps matter, but not a rigorous attribution:
+    /// correlated taps can cancel or share their contributions. All importances are zero if no
+    /// tap contributes any variance.
+    pub fn feature_importance(&self) -> Array1<F> {
+        let start = self.weight.len() - self.input_len();
+        let (_, weight) = self.weight.view().split_at(Axis(0), start);
+        let variance = self.input_statistics.variance();
+        let (_, variance) = variance.view().split_at(Axis(0), start);
 
-                self.temp_mat.fill(0.0);
-                let temp_mat_stride = self.temp_mat.strides()[0];
-                unsafe {
-                    $fn(
-                        blas::c::Layout::RowMajor,
-                        self.gain.dim() as i32,
-                        self.temp_vec.dim() as i32,
-                        1.0,
-                        self.gain.as_slice().unwrap(),
-                        self.gain.strides()[0] as i32,
-                        self.temp_vec.as_slice().unwrap(),
-                        self.gain.strides()[0] as i32,
-                        self.temp_mat.as_slice_mut().unwrap(),
-                        temp_mat_stride as i32,
-                    );
-                }
-                self.inverse_correlation -= &self.temp_mat;
-                self.inverse_correlation *= self.inv_forgetting_factor;
-            }
+        let mut importance = weight.mapv(|w| w * w);
+        importance *= &variance;
+        let total = importance.scalar_sum();
+        if total > F::zero() {
+            importance /= total;
         }
-}}
+        importance
+    }
 
-impl_update!(f32, blas::c::sger);
-impl_update!(f64, blas::c::dger);
+    /// Maps the weights learned on transformed inputs z(i) = T u(i), e.g. after whitening or a
+    /// PCA projection, back to the original inputs u(i), returning Tᵀ w, so that
+    /// w · z(i) = (Tᵀ w) · u(i).
+    ///
+    /// `transform` is the matrix T of shape `(n_taps, n_original)` applied to the original
+    /// inputs, i.e. row j of T yields tap j. An [intercept](#method.with_bias) is not
+    /// transformed and omitted.
+    ///
+    /// **Panics** if the number of rows of `transform` does not match the number of taps.
+    pub fn weights_in_original_space<S>(&self, transform: &ArrayBase<S, Ix2>) -> Array1<F>
+        where S: Data<Elem = F>
+    {
+        assert_eq!(transform.rows(), self.input_len(),
+            "transform with {} rows does not match weight of length {}",
+            transform.rows(), self.input_len());
+        transform.t().dot(&self.impulse_response())
+    }
 
-impl<T> Rls<T> {
+    /// Returns the impulse response of the learned FIR filter, i.e. a copy of the tap weights,
+    /// with the response to x(i-k) at index k. An [intercept](#method.with_bias) is omitted.
+    pub fn impulse_response(&self) -> Array1<F> {
+        let start = self.weight.len() - self.input_len();
+        self.weight.view().split_at(Axis(0), start).1.to_owned()
+    }
 
-    /// Returns a reference to the gain vector.
-    pub fn gain_ref(&self) -> &Array1<T> {
-        &self.gain
+    /// Returns the step response of the learned FIR filter, the cumulative sum of the tap weights
+    /// with the response k samples after the step at index k. An [intercept](#method.with_bias)
+    /// is omitted.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate ndarray;
+    /// # extern crate recless;
+    /// use ndarray::arr1;
+    /// use recless::Rls;
+    ///
+    /// # fn main() {
+    /// let mut rls = Rls::<f64>::new(0.1, 1.0, 3);
+    /// rls.set_weight(arr1(&[0.5, 0.25, -0.75]), None).unwrap();
+    ///
+    /// assert_eq!(rls.impulse_response(), arr1(&[0.5, 0.25, -0.75]));
+    /// assert_eq!(rls.step_response(), arr1(&[0.5, 0.75, 0.0]));
+    /// # }
+    /// ```
+    pub fn step_response(&self) -> Array1<F> {
+        let mut response = self.impulse_response();
+        let mut sum = F::zero();
+        for x in response.iter_mut() {
+            sum += *x;
+            *x = sum;
+        }
+        response
     }
 
-    /// Returns a reference to the inverse correlation matrix.
-    pub fn inverse_correlation_ref(&self) -> &Array2<T> {
-        &self.inverse_correlation
+    /// Constructs a new leaky Rls object with initialization factor δ, a weight vector of length
+    /// n, and leakage γ.
+    ///
+    /// Each update shrinks the weight vector towards zero as w(i) = (1 - λ γ) w(i-1) + k(i) e(i),
+    /// which keeps the weights from drifting under insufficient excitation. With γ = 0 this is
+    /// the standard update.
+    pub fn with_leakage(initialization_factor: F, forgetting_factor: F, n: usize, leakage: F) -> Self {
+        let mut rls = Rls::new(initialization_factor, forgetting_factor, n);
+        rls.leakage = leakage;
+        rls
     }
 
-    /// Returns a reference to the inverse forgetting factor.
-    pub fn inv_forgetting_factor_ref(&self) -> &T {
-        &self.inv_forgetting_factor
+    /// Constructs a new Rls object with initialization factor δ, a weight vector of length n,
+    /// and separate forgetting factors for the gain and the inverse correlation matrix.
+    ///
+    /// Each update computes the gain as k(i) = P(i-1) u(i) / (λ_k^{-1} + u(i) · P(i-1) u(i))
+    /// and rescales the inverse correlation matrix as
+    /// P(i) = λ_P^{-1} (P(i-1) - k(i) (P(i-1)ᵀ u(i))ᵀ). With λ_k = λ_P this is the standard
+    /// update. The [forgetting factor](#method.forgetting_factor) and the ways to set or adapt it
+    /// refer to λ_k, and [`time_update`](#method.time_update) uses λ_P. Directional forgetting
+    /// uses λ_k throughout.
+    ///
+    /// *Note:* With λ_k ≠ λ_P the filter no longer minimizes an exponentially weighted least
+    /// squares cost. Under persistent excitation P(i) settles at a level set by both factors;
+    /// with λ_P = 1 it decays to zero and the filter stops adapting whatever λ_k, while a small
+    /// λ_P lets P(i) grow quickly in directions that are not excited.
+    pub fn with_dual_forgetting(initialization_factor: F, gain_forgetting_factor: F, covariance_forgetting_factor: F, n: usize)
+        -> Self
+    {
+        let mut rls = Rls::new(initialization_factor, gain_forgetting_factor, n);
+        rls.inv_covariance_forgetting_factor = Some(F::one() / covariance_forgetting_factor);
+        rls
     }
 
-    /// Returns a reference to the (tap) weight vector.
-    pub fn weight_ref(&self) -> &Array1<T> {
-        &self.weight
+    /// Returns the forgetting factor λ_P of the inverse correlation matrix, which equals the
+    /// [forgetting factor](#method.forgetting_factor) unless set by
+    /// [`with_dual_forgetting`](#method.with_dual_forgetting).
+    pub fn covariance_forgetting_factor(&self) -> F {
+        F::one() / self.inv_covariance_forgetting_factor()
     }
 
-    /// Returns a refernce to the prior error.
-    pub fn prior_error_ref(&self) -> &T {
-        &self.prior_error
+    /// Returns the inverse forgetting factor λ_P^{-1} the inverse correlation matrix is rescaled
+    /// with.
+    fn inv_covariance_forgetting_factor(&self) -> F {
+        self.inv_covariance_forgetting_factor.unwrap_or(self.inv_forgetting_factor)
+    }
+
+    /// Constructs a new Rls object with initialization factor δ for inputs of length n and an
+    /// intercept.
+    ///
+    /// The weight vector has length n + 1, with the intercept at index 0: `update` and `predict`
+    /// take inputs of length n and prepend the constant 1 internally.
+    pub fn with_bias(initialization_factor: F, forgetting_factor: F, n: usize) -> Self {
+        let mut rls = Rls::new(initialization_factor, forgetting_factor, n + 1);
+        rls.enable_bias();
+        rls
+    }
+
+    /// Treats the first tap as the intercept, see [`with_bias`](#method.with_bias).
+    fn enable_bias(&mut self) {
+        self.bias = true;
+        self.bias_input = Array1::zeros(self.weight.len());
+    }
+
+    /// Returns `true` if the first tap is an intercept, see [`with_bias`](#method.with_bias).
+    pub fn has_bias(&self) -> bool {
+        self.bias
+    }
+
+    /// Returns the length of the inputs, i.e. the number of taps without the intercept.
+    fn input_len(&self) -> usize {
+        if self.bias {
+            self.weight.len() - 1
+        } else {
+            self.weight.len()
+        }
+    }
+
+    /// Constructs a new regularized Rls object with initialization factor δ, a weight vector of
+    /// length n, and Tikhonov regularization μ.
+    ///
+    /// The filter minimizes the exponentially weighted squared errors plus μ |w|². The inverse
+    /// correlation matrix is initialized as P = (δ + μ)^{-1} · 𝟙, and since forgetting also
+    /// decays the regularization, each update adds it back as the rank-1 term
+    /// n (1 - λ) μ e_j e_jᵀ for one tap j after the other. Over n updates this restores μ · 𝟙,
+    /// at the cost of a second rank-1 update per step. With μ = 0 this is the standard update.
+    pub fn with_regularization(initialization_factor: F, forgetting_factor: F, n: usize, regularization: F)
+        -> Self
+    {
+        let mut rls = Rls::new(initialization_factor + regularization, forgetting_factor, n);
+        rls.initialization_factor = initialization_factor;
+        rls.regularization = regularization;
+        rls
+    }
+
+    /// Reinitializes the filter with initialization factor δ, reusing the allocated buffers.
+    ///
+    /// The weight vector is set to zero and the inverse correlation matrix to P = δ^{-1} · 𝟙, or
+    /// P = (δ + μ)^{-1} · 𝟙 with [regularization](#method.with_regularization) μ. The forgetting
+    /// factor is left unchanged.
+    pub fn reset(&mut self, initialization_factor: F) {
+        let one = F::one();
+        let zero = F::zero();
+
+        self.initialization_factor = initialization_factor;
+
+        self.gain.fill(zero);
+        self.weight.fill(zero);
+        self.prior_error = zero;
+        self.temp_vec.fill(zero);
+        self.residual_energy = zero;
+        self.residual_weight = zero;
+        self.target_sum = zero;
+        self.target_energy = zero;
+        self.updates_since_symmetrize = 0;
+        self.weight_delta_norm = zero;
+        self.regularization_tap = 0;
+        self.num_updates = 0;
+        if let Some(ref mut window) = self.error_window {
+            window.clear();
+        }
+        if let Some(ref mut history) = self.weight_history {
+            history.clear();
+        }
+        self.restart_weight_smoothing();
+        self.input_statistics.clear();
+
+        self.inverse_correlation.fill(zero);
+        self.inverse_correlation.diag_mut().fill(one/(initialization_factor + self.regularization));
+    }
+
+    /// Combines the filter with `other` by replacing the weight vector and the inverse
+    /// correlation matrix with their convex combinations, weighted by `weight_self` and
+    /// `weight_other` normalized to sum to 1, e.g. the numbers of samples each filter was
+    /// trained on.
+    ///
+    /// *Note:* This is a heuristic for model averaging, e.g. of filters trained on shards of the
+    /// data, and not the RLS solution for the combined data.
+    ///
+    /// The smoothed weight vector, if enabled, restarts at the merged weight vector.
+    ///
+    /// Returns an error without modifying the filter if the numbers of taps, the forgetting
+    /// factors or the presence of a bias term do not match.
+    ///
+    /// **Panics** if `weight_self` or `weight_other` is negative, or if both are zero.
+    pub fn merge(&mut self, other: &Self, weight_self: F, weight_other: F) -> Result<(), RlsError> {
+        assert!(weight_self >= F::zero() && weight_other >= F::zero()
+            && weight_self + weight_other > F::zero(),
+            "merge weights must be non-negative and not both zero");
+
+        if other.weight.len() != self.weight.len() {
+            return Err(RlsError::DimensionMismatch {
+                expected: self.weight.len(),
+                got: other.weight.len(),
+            });
+        }
+        if other.inv_forgetting_factor != self.inv_forgetting_factor
+            || other.inv_covariance_forgetting_factor() != self.inv_covariance_forgetting_factor()
+        {
+            return Err(RlsError::ForgettingFactorMismatch);
+        }
+        if other.bias != self.bias {
+            return Err(RlsError::BiasMismatch);
+        }
+
+        let total = weight_self + weight_other;
+        let (alpha, beta) = (weight_self / total, weight_other / total);
+
+        self.weight *= alpha;
+        self.weight.scaled_add(beta, &other.weight);
+
+        self.inverse_correlation *= alpha;
+        self.inverse_correlation.scaled_add(beta, &other.inverse_correlation);
+        self.restart_weight_smoothing();
+
+        Ok(())
+    }
+
+    /// Appends `extra` taps to the filter, e.g. to increase the model order online while keeping
+    /// the learned state.
+    ///
+    /// The new weights are zero. The inverse correlation matrix keeps its old block in the top
+    /// left and is extended with δ^{-1} on the new diagonal and zeros elsewhere.
+    ///
+    /// *Note:* This only approximates reinitializing the new dimensions, since it ignores the
+    /// correlation of the new taps with the old ones in the data seen so far.
+    pub fn grow(&mut self, extra: usize, initialization_factor: F) {
+        if extra == 0 {
+            return;
+        }
+        let n = self.weight.len();
+        let m = n + extra;
+
+        let mut weight = Array1::zeros(m);
+        weight.view_mut().split_at(Axis(0), n).0.assign(&self.weight);
+        self.weight = weight;
+
+        let mut inverse_correlation = Array2::zeros([m, m]);
+        {
+            let (top, bottom) = inverse_correlation.view_mut().split_at(Axis(0), n);
+            top.split_at(Axis(1), n).0.assign(&self.inverse_correlation);
+            bottom.split_at(Axis(1), n).1.diag_mut().fill(F::one()/initialization_factor);
+        }
+        self.inverse_correlation = inverse_correlation;
+
+        self.gain = Array1::zeros(m);
+        self.temp_vec = Array1::zeros(m);
+        if self.bias {
+            self.bias_input = Array1::zeros(m);
+        }
+        self.restart_weight_smoothing();
+        self.input_statistics.grow(extra);
+    }
+
+    /// Removes the taps at `indices` from the filter, e.g. those with negligible weights, along
+    /// with the corresponding rows and columns of the inverse correlation matrix.
+    ///
+    /// The inputs of subsequent updates and predictions must omit the removed taps. With an
+    /// [intercept](#method.with_bias), the indices refer to the weight vector, which holds the
+    /// intercept at index 0.
+    ///
+    /// **Panics** if an index is out of range or given more than once, or if it refers to the
+    /// intercept.
+    pub fn prune(&mut self, indices: &[usize]) {
+        let n = self.weight.len();
+        let mut removed = Array1::from_elem(n, false);
+        for &i in indices {
+            assert!(i < n, "tap index {} out of range for weight of length {}", i, n);
+            assert!(!removed[i], "tap index {} given more than once", i);
+            assert!(!(self.bias && i == 0), "the intercept at index 0 cannot be pruned");
+            removed[i] = true;
+        }
+        if indices.is_empty() {
+            return;
+        }
+
+        let keep: Vec<usize> = (0..n).filter(|&i| !removed[i]).collect();
+        let m = keep.len();
+
+        self.weight = self.weight.select(Axis(0), &keep);
+        self.inverse_correlation = self.inverse_correlation
+            .select(Axis(0), &keep)
+            .select(Axis(1), &keep);
+
+        self.gain = Array1::zeros(m);
+        self.temp_vec = Array1::zeros(m);
+        if self.bias {
+            self.bias_input = Array1::zeros(m);
+        }
+        self.regularization_tap = 0;
+        self.restart_weight_smoothing();
+        self.input_statistics.select(&keep);
+    }
+
+    /// Returns `true` if the weight vectors, the inverse correlation matrices, the forgetting
+    /// factors, and the prior errors of both filters agree element-wise within `tol`.
+    ///
+    /// This is the right tool for comparing filters after floating point computations, e.g.
+    /// after a round trip through serialization or [`to_f32`](#method.to_f32), where the exact
+    /// comparison by `==` is too strict. Filters with different numbers of taps are never equal.
+    pub fn approx_eq(&self, other: &Self, tol: F) -> bool {
+        if self.weight.len() != other.weight.len() {
+            return false;
+        }
+        let close = |a: F, b: F| (a - b).abs() <= tol;
+
+        close(self.inv_forgetting_factor, other.inv_forgetting_factor)
+            && close(self.prior_error, other.prior_error)
+            && self.weight.iter().zip(other.weight.iter()).all(|(&a, &b)| close(a, b))
+            && self.inverse_correlation.iter()
+                .zip(other.inverse_correlation.iter())
+                .all(|(&a, &b)| close(a, b))
+    }
+
+    /// Returns a copy of the filter with the same hyperparameters, but reinitialized with the
+    /// initialization factor δ it was constructed or last [`reset`](#method.reset) with.
+    pub fn clone_reset(&self) -> Self {
+        let mut rls = self.clone();
+        rls.reset(self.initialization_factor);
+        rls
+    }
+
+    /// Captures the state mutated by an update, so that it can be rolled back with
+    /// [`restore`](#method.restore), e.g. after a speculative update.
+    ///
+    /// Besides weight, gain, inverse correlation matrix, and prior error, this includes the
+    /// forgetting factor and the bookkeeping of the residual and target statistics, so that a
+    /// restored filter continues exactly as if the rolled back updates never happened. Weight
+    /// vectors recorded since the snapshot are discarded on restore. The configuration, e.g.
+    /// leakage or a forgetting factor policy, is not captured.
+    pub fn snapshot(&self) -> RlsSnapshot<F> {
+        RlsSnapshot {
+            weight: self.weight.clone(),
+            gain: self.gain.clone(),
+            inverse_correlation: self.inverse_correlation.clone(),
+            prior_error: self.prior_error,
+            inv_forgetting_factor: self.inv_forgetting_factor,
+            residual_energy: self.residual_energy,
+            residual_weight: self.residual_weight,
+            target_sum: self.target_sum,
+            target_energy: self.target_energy,
+            updates_since_symmetrize: self.updates_since_symmetrize,
+            weight_delta_norm: self.weight_delta_norm,
+            regularization_tap: self.regularization_tap,
+            num_updates: self.num_updates,
+            error_window: self.error_window.clone(),
+            history_len: self.weight_history.as_ref().map_or(0, Vec::len),
+            weight_smoothing: self.weight_smoothing.clone(),
+            input_statistics: self.input_statistics.clone(),
+        }
+    }
+
+    /// Rolls the filter back to the state captured by [`snapshot`](#method.snapshot).
+    ///
+    /// **Panics** if the snapshot was taken of a filter with a different number of taps.
+    pub fn restore(&mut self, snapshot: RlsSnapshot<F>) {
+        assert_eq!(snapshot.weight.len(), self.weight.len(),
+            "snapshot of length {} does not match weight of length {}",
+            snapshot.weight.len(), self.weight.len());
+        self.weight = snapshot.weight;
+        self.gain = snapshot.gain;
+        self.inverse_correlation = snapshot.inverse_correlation;
+        self.prior_error = snapshot.prior_error;
+        self.inv_forgetting_factor = snapshot.inv_forgetting_factor;
+        self.residual_energy = snapshot.residual_energy;
+        self.residual_weight = snapshot.residual_weight;
+        self.target_sum = snapshot.target_sum;
+        self.target_energy = snapshot.target_energy;
+        self.updates_since_symmetrize = snapshot.updates_since_symmetrize;
+        self.weight_delta_norm = snapshot.weight_delta_norm;
+        self.regularization_tap = snapshot.regularization_tap;
+        self.num_updates = snapshot.num_updates;
+        self.error_window = snapshot.error_window;
+        if let Some(ref mut history) = self.weight_history {
+            history.truncate(snapshot.history_len);
+        }
+        self.weight_smoothing = snapshot.weight_smoothing;
+        self.input_statistics = snapshot.input_statistics;
+    }
+
+    /// Skips updates whose gain denominator c = λ^{-1} + u(i) · P(i-1) u(i) is smaller than
+    /// `min_denominator` in magnitude, or never skips them with `min_denominator` = 0, the
+    /// default.
+    ///
+    /// The gain is P(i-1) u(i) / c, so a tiny c produces a huge gain and correction of the
+    /// weights. For a positive definite P(i-1), c ≥ λ^{-1} ≥ 1, but once round-off has made P
+    /// indefinite, or for a sample weighted up by a large observation weight in
+    /// [`update_weighted`](#method.update_weighted), c can approach zero and the update would
+    /// destabilize the filter. A skipped update makes [`try_update`](#method.try_update) return
+    /// [`RlsError::SmallDenominator`] without modifying the filter, and `update` panic. The check
+    /// costs an additional matrix-vector product per update.
+    ///
+    /// **Panics** if `min_denominator` is negative.
+    ///
+    /// [`RlsError::SmallDenominator`]: enum.RlsError.html#variant.SmallDenominator
+    pub fn set_min_denominator(&mut self, min_denominator: F) {
+        assert!(min_denominator >= F::zero(),
+            "minimum denominator must be non-negative, got {}", min_denominator);
+        self.min_denominator = min_denominator;
+    }
+
+    /// Freezes the filter: subsequent updates only compute and return the prior error, leaving
+    /// gain, inverse correlation matrix, and weight vector unchanged.
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    /// Unfreezes the filter, so that updates adapt it again.
+    pub fn unfreeze(&mut self) {
+        self.frozen = false;
+    }
+
+    /// Returns `true` if the filter is frozen, see [`freeze`](#method.freeze).
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Returns the initialization factor δ the filter was constructed or last
+    /// [`reset`](#method.reset) with, and which [`clone_reset`](#method.clone_reset) uses.
+    ///
+    /// A filter assembled with [`from_parts`](#method.from_parts) reports the
+    /// [`DEFAULT_INITIALIZATION_FACTOR`](constant.DEFAULT_INITIALIZATION_FACTOR.html).
+    /// [`reset_covariance`](#method.reset_covariance) does not change it.
+    pub fn initialization_factor(&self) -> F {
+        self.initialization_factor
+    }
+
+    /// Returns the forgetting factor λ.
+    pub fn forgetting_factor(&self) -> F {
+        F::one() / self.inv_forgetting_factor
+    }
+
+    /// Returns the effective memory 1 / (1 - λ) of the exponential forgetting, i.e. the
+    /// approximate number of past samples the filter averages over, or infinity for λ = 1.
+    ///
+    /// This is the time constant of the exponential window, e.g. 10 samples for λ = 0.9 and 100
+    /// samples for λ = 0.99.
+    ///
+    /// ```
+    /// # extern crate recless;
+    /// use recless::Rls;
+    ///
+    /// # fn main() {
+    /// let memory = |forgetting_factor: f64| Rls::new(0.01, forgetting_factor, 1).effective_memory();
+    ///
+    /// assert!((memory(0.5) - 2.0).abs() < 1e-12);
+    /// assert!((memory(0.9) - 10.0).abs() < 1e-12);
+    /// assert!((memory(0.99) - 100.0).abs() < 1e-9);
+    /// assert!(memory(1.0).is_infinite());
+    /// # }
+    /// ```
+    pub fn effective_memory(&self) -> F {
+        let forgetting_factor = self.forgetting_factor();
+        if forgetting_factor >= F::one() {
+            F::infinity()
+        } else {
+            F::one() / (F::one() - forgetting_factor)
+        }
+    }
+
+    /// Sets the forgetting factor λ, e.g. to ramp it towards 1 over time.
+    ///
+    /// **Panics** unless 0 < λ ≤ 1.
+    pub fn set_forgetting_factor(&mut self, forgetting_factor: F) {
+        assert!(forgetting_factor > F::zero() && forgetting_factor <= F::one(),
+            "forgetting factor must satisfy 0 < λ <= 1, got {}", forgetting_factor);
+        self.inv_forgetting_factor = F::one() / forgetting_factor;
+    }
+
+    /// Adapts the forgetting factor before each update to the prior error.
+    ///
+    /// Before each update, the forgetting factor is recomputed from the normalized squared prior
+    /// error ε(i) = e(i)² / (1 + u(i) · P(i-1) u(i)) as
+    ///
+    /// λ(i) = λ_max - (λ_max - λ_min) · (1 - exp(-s · ε(i))),
+    ///
+    /// with s the sensitivity. A large prior error shrinks λ towards λ_min for fast adaptation,
+    /// while a small prior error relaxes λ towards λ_max for low misadjustment. The current
+    /// forgetting factor can be read with [`forgetting_factor`](#method.forgetting_factor).
+    ///
+    /// **Panics** unless 0 < λ_min ≤ λ_max ≤ 1 and the sensitivity is non-negative.
+    pub fn set_vff_policy(&mut self, min_forgetting_factor: F, max_forgetting_factor: F, sensitivity: F) {
+        assert!(min_forgetting_factor > F::zero()
+            && min_forgetting_factor <= max_forgetting_factor
+            && max_forgetting_factor <= F::one(),
+            "forgetting factors must satisfy 0 < λ_min <= λ_max <= 1, got λ_min = {}, λ_max = {}",
+            min_forgetting_factor, max_forgetting_factor);
+        assert!(sensitivity >= F::zero(),
+            "sensitivity must be non-negative, got {}", sensitivity);
+        self.vff_policy = Some(VffPolicy {
+            min_forgetting_factor,
+            max_forgetting_factor,
+            sensitivity,
+        });
+    }
+
+    /// Stops adapting the forgetting factor, keeping its current value.
+    pub fn clear_vff_policy(&mut self) {
+        self.vff_policy = None;
+    }
+
+    /// Schedules the forgetting factor over the number of updates, for fast initial adaptation
+    /// that smoothly transitions to slow steady-state forgetting.
+    ///
+    /// Before the update following i = [`num_updates`](#method.num_updates) updates, the
+    /// forgetting factor is set to
+    ///
+    /// λ(i) = λ_∞ - (λ_∞ - λ_0) · exp(-r · i),
+    ///
+    /// with r the rate, so that it starts out at λ_0 and approaches λ_∞. The current forgetting
+    /// factor can be read with [`forgetting_factor`](#method.forgetting_factor). A
+    /// [forgetting factor policy](#method.set_vff_policy), if any, takes precedence.
+    ///
+    /// **Panics** unless 0 < λ_0 ≤ 1, 0 < λ_∞ ≤ 1, and the rate is non-negative.
+    pub fn set_forgetting_schedule(&mut self, initial_forgetting_factor: F, final_forgetting_factor: F, rate: F) {
+        assert!(initial_forgetting_factor > F::zero() && initial_forgetting_factor <= F::one()
+            && final_forgetting_factor > F::zero() && final_forgetting_factor <= F::one(),
+            "forgetting factors must satisfy 0 < λ <= 1, got λ_0 = {}, λ_∞ = {}",
+            initial_forgetting_factor, final_forgetting_factor);
+        assert!(rate >= F::zero(),
+            "rate must be non-negative, got {}", rate);
+        self.forgetting_schedule = Some(ForgettingSchedule {
+            initial_forgetting_factor,
+            final_forgetting_factor,
+            rate,
+        });
+    }
+
+    /// Stops scheduling the forgetting factor, keeping its current value.
+    pub fn clear_forgetting_schedule(&mut self) {
+        self.forgetting_schedule = None;
+    }
+
+    /// Returns the estimate of the residual variance.
+    ///
+    /// The estimate is the exponentially weighted mean Σ λ^{i-k} e_post(k)² / Σ λ^{i-k} of the
+    /// squared posterior errors e_post(k) = d(k) - w(k) · u(k), using the same forgetting factor
+    /// as the filter. It is zero before the first update.
+    pub fn residual_variance(&self) -> F {
+        if self.residual_weight > F::zero() {
+            self.residual_energy / self.residual_weight
+        } else {
+            F::zero()
+        }
+    }
+
+    /// Returns the exponentially weighted coefficient of determination
+    /// R² = 1 - σ_e² / σ_d², with the [residual variance](#method.residual_variance) σ_e² and the
+    /// variance σ_d² of the targets, both weighted with the forgetting factor of the filter.
+    ///
+    /// Close to 1, the filter explains most of the variation of the targets. Returns zero before
+    /// the first update, or if the targets are constant up to round-off, where R² is undefined.
+    pub fn r_squared(&self) -> F {
+        if self.residual_weight <= F::zero() {
+            return F::zero();
+        }
+        let mean = self.target_sum / self.residual_weight;
+        let mean_square = self.target_energy / self.residual_weight;
+        let target_variance = mean_square - mean * mean;
+        if target_variance <= F::epsilon() * mean_square {
+            return F::zero();
+        }
+        F::one() - self.residual_variance() / target_variance
+    }
+
+    /// Returns the root mean square of the last K prior errors, with the window length K set by
+    /// [`RlsBuilder::error_window`](struct.RlsBuilder.html#method.error_window).
+    ///
+    /// This is a smoother convergence signal than the prior error of a single update. Updates of
+    /// a frozen filter are included. Returns zero before the first update or if the error window
+    /// is disabled.
+    pub fn windowed_rmse(&self) -> F {
+        self.error_window.as_ref().map_or(F::zero(), ErrorWindow::rmse)
+    }
+
+    /// Returns the weight vectors w(i) after each update since construction or the last
+    /// [`reset`](#method.reset), if enabled by
+    /// [`RlsBuilder::record_history`](struct.RlsBuilder.html#method.record_history), and an
+    /// empty slice otherwise.
+    ///
+    /// Updates of a frozen filter are not recorded.
+    pub fn weight_history(&self) -> &[Array1<F>] {
+        self.weight_history.as_ref().map_or(&[], |history| &history[..])
+    }
+
+    /// Discards the recorded weight vectors, keeping the recording enabled.
+    pub fn clear_weight_history(&mut self) {
+        if let Some(ref mut history) = self.weight_history {
+            history.clear();
+        }
+    }
+
+    /// Returns the exponentially smoothed weight vector
+    /// w̄(i) = α w(i) + (1 - α) w̄(i-1), if enabled by
+    /// [`RlsBuilder::weight_smoothing`](struct.RlsBuilder.html#method.weight_smoothing), and
+    /// the weight vector w(i) otherwise.
+    ///
+    /// Under noisy targets, w(i) jitters around the true weights, and the smoothed weights are a
+    /// lower-variance read-out, e.g. for deployment, at the cost of lagging behind changes by
+    /// about 1 / α updates. The adaptation of the filter itself is not affected, and with α = 1,
+    /// w̄(i) equals w(i). The smoothing starts out at the weights after construction, a
+    /// [`reset`](#method.reset), [`set_weight`](#method.set_weight), [`merge`](#method.merge),
+    /// [`grow`](#method.grow), or [`prune`](#method.prune), and only follows changes of the
+    /// weights by updates.
+    pub fn smoothed_weight(&self) -> &Array1<F> {
+        self.weight_smoothing.as_ref().map_or(&self.weight, |smoothing| &smoothing.weight)
+    }
+
+    /// Restarts the smoothed weight vector, if enabled, at the current weight vector.
+    fn restart_weight_smoothing(&mut self) {
+        if let Some(ref mut smoothing) = self.weight_smoothing {
+            smoothing.weight = self.weight.clone();
+        }
+    }
+
+    /// Returns the number of updates applied since construction or the last
+    /// [`reset`](#method.reset). Updates of a frozen filter and failed updates are not counted.
+    pub fn num_updates(&self) -> u64 {
+        self.num_updates
+    }
+
+    /// Returns the Euclidean norm |e(i) k(i)| of the most recent weight correction, or zero before
+    /// the first update.
+    ///
+    /// On a stationary system this decays as the filter converges.
+    pub fn last_weight_delta_norm(&self) -> F {
+        self.weight_delta_norm
+    }
+
+    /// Returns `true` if the norm of the most recent weight correction is below `tol`, see
+    /// [`last_weight_delta_norm`](#method.last_weight_delta_norm).
+    pub fn has_converged(&self, tol: F) -> bool {
+        self.weight_delta_norm < tol
+    }
+
+    /// Replaces the inverse correlation matrix P by (P + Pᵀ) / 2 in place.
+    ///
+    /// Round-off in the rank-1 updates slowly makes P asymmetric, which degrades the stability of
+    /// long-running filters.
+    pub fn symmetrize(&mut self) {
+        let two = F::one() + F::one();
+        let n = self.inverse_correlation.rows();
+        for i in 0..n {
+            for j in (i + 1)..n {
+                let mean = (self.inverse_correlation[[i, j]] + self.inverse_correlation[[j, i]]) / two;
+                self.inverse_correlation[[i, j]] = mean;
+                self.inverse_correlation[[j, i]] = mean;
+            }
+        }
+        self.updates_since_symmetrize = 0;
+    }
+
+    /// Symmetrizes the inverse correlation matrix automatically after every `k` updates, see
+    /// [`symmetrize`](#method.symmetrize). With `k = 0`, it is never symmetrized automatically.
+    pub fn set_symmetrize_every(&mut self, k: usize) {
+        self.symmetrize_every = k;
+    }
+
+    /// Resets the inverse correlation matrix to P = δ^{-1} · 𝟙 while keeping the weight vector.
+    ///
+    /// After a long run with a forgetting factor close to 1, P can become so small that the
+    /// filter effectively stops adapting (covariance windup). Resetting it lets the filter track
+    /// changes again, starting from the current weights.
+    pub fn reset_covariance(&mut self, initialization_factor: F) {
+        self.inverse_correlation.fill(F::zero());
+        self.inverse_correlation.diag_mut().fill(F::one()/initialization_factor);
+        self.updates_since_symmetrize = 0;
+    }
+
+    /// Returns `true` if the inverse correlation matrix is positive definite up to round-off,
+    /// i.e. if the Cholesky factorization of its symmetric part (P + Pᵀ) / 2 succeeds.
+    ///
+    /// Round-off in long runs can make P lose its positive definiteness, which silently
+    /// corrupts the gain and eventually the weights, see
+    /// [`repair_covariance`](#method.repair_covariance).
+    pub fn is_positive_definite(&self) -> bool {
+        let symmetric = (&self.inverse_correlation + &self.inverse_correlation.t()) / (F::one() + F::one());
+        cholesky(&symmetric, false).is_some()
+    }
+
+    /// Restores the positive definiteness of the inverse correlation matrix, keeping the
+    /// weight vector, and returns `true` if P had to be repaired.
+    ///
+    /// If P is not [positive definite](#method.is_positive_definite), it is replaced by the
+    /// symmetric matrix with the eigenvectors of (P + Pᵀ) / 2 and its eigenvalues floored at
+    /// ε^{1/2} times the largest one, with ε the machine epsilon, which keeps the information in
+    /// the well-determined directions. If P has no positive eigenvalue or a non-finite entry,
+    /// it is [reset](#method.reset_covariance) to δ^{-1} · 𝟙 instead.
+    pub fn repair_covariance(&mut self, initialization_factor: F) -> bool {
+        if self.is_positive_definite() {
+            return false;
+        }
+
+        let finite = self.inverse_correlation.iter().all(|p| p.is_finite());
+        if finite {
+            self.symmetrize();
+            let (mut values, vectors) = symmetric_eigen(self.inverse_correlation.clone());
+            let max = values.iter().fold(F::zero(), |max, &v| max.max(v));
+            if max > F::zero() {
+                let floor = F::epsilon().sqrt() * max;
+                values.mapv_inplace(|v| v.max(floor));
+
+                let mut scaled = vectors.clone();
+                for (mut column, &v) in scaled.axis_iter_mut(Axis(1)).zip(values.iter()) {
+                    column *= v;
+                }
+                self.inverse_correlation = scaled.dot(&vectors.t());
+                self.symmetrize();
+                if self.is_positive_definite() {
+                    return true;
+                }
+            }
+        }
+
+        self.reset_covariance(initialization_factor);
+        true
+    }
+
+    /// Initializes the inverse correlation matrix from a batch of representative inputs instead
+    /// of δ^{-1} · 𝟙 and sets the weight vector to zero, e.g. before going online.
+    ///
+    /// P is set to the inverse of the regularized correlation matrix Φ = Σ u uᵀ + ρ 𝟙 of the
+    /// rows u of `inputs` and the ridge ρ, scaled by λ^{-2} as P(i) = λ^{-2} Φ(i)^{-1} is in
+    /// the convention of this filter. Since P then reflects the actual scale and correlation
+    /// of the inputs, the filter converges much faster than from the diagonal initialization.
+    /// See [`warm_start_fit`](#method.warm_start_fit) to also fit the weights to the batch.
+    ///
+    /// Returns [`RlsError::SingularCorrelation`] without modifying the filter if Φ is not
+    /// positive definite, which a positive ridge rules out.
+    ///
+    /// **Panics** if `ridge` is negative, or if the number of columns of `inputs` does not match
+    /// the length of the weight vector.
+    ///
+    /// [`RlsError::SingularCorrelation`]: enum.RlsError.html#variant.SingularCorrelation
+    pub fn warm_start<S>(&mut self, inputs: &ArrayBase<S, Ix2>, ridge: F) -> Result<(), RlsError>
+        where S: Data<Elem = F>
+    {
+        let targets = Array1::zeros(inputs.rows());
+        self.warm_start_fit(inputs, &targets, ridge)
+    }
+
+    /// Initializes the inverse correlation matrix from a batch like
+    /// [`warm_start`](#method.warm_start), and sets the weight vector to the ridge regression
+    /// solution w = Φ^{-1} Σ u d for the rows u of `inputs` and the elements d of `targets`.
+    ///
+    /// Returns [`RlsError::SingularCorrelation`] without modifying the filter if Φ is not
+    /// positive definite.
+    ///
+    /// **Panics** if `ridge` is negative, if the number of columns of `inputs` does not match
+    /// the length of the weight vector, or if the length of `targets` does not match the number
+    /// of rows of `inputs`.
+    ///
+    /// [`RlsError::SingularCorrelation`]: enum.RlsError.html#variant.SingularCorrelation
+    pub fn warm_start_fit<S1, S2>(&mut self, inputs: &ArrayBase<S1, Ix2>, targets: &ArrayBase<S2, Ix1>, ridge: F)
+        -> Result<(), RlsError>
+        where S1: Data<Elem = F>,
+              S2: Data<Elem = F>,
+    {
+        assert!(ridge >= F::zero(),
+            "ridge must be non-negative, got {}", ridge);
+        assert_eq!(inputs.cols(), self.input_len(),
+            "inputs with {} columns do not match weight of length {}",
+            inputs.cols(), self.input_len());
+        assert_eq!(targets.len(), inputs.rows(),
+            "targets of length {} do not match {} inputs",
+            targets.len(), inputs.rows());
+
+        // The regressors, with a column of ones for an intercept.
+        let n = self.weight.len();
+        let mut regressors = Array2::from_elem((inputs.rows(), n), F::one());
+        regressors.view_mut().split_at(Axis(1), n - self.input_len()).1.assign(inputs);
+
+        let mut correlation = regressors.t().dot(&regressors);
+        for x in correlation.diag_mut() {
+            *x += ridge;
+        }
+        let mut solution = Array2::zeros((n, n + 1));
+        {
+            let (mut identity, mut cross_correlation) = solution.view_mut().split_at(Axis(1), n);
+            identity.diag_mut().fill(F::one());
+            cross_correlation.column_mut(0).assign(&regressors.t().dot(targets));
+        }
+        if !solve_positive_definite(&correlation, &mut solution) {
+            return Err(RlsError::SingularCorrelation);
+        }
+
+        let (inverse_correlation, weight) = solution.view().split_at(Axis(1), n);
+        let inv_forgetting_factor = self.inv_forgetting_factor;
+        self.inverse_correlation.assign(&inverse_correlation);
+        self.inverse_correlation *= inv_forgetting_factor * inv_forgetting_factor;
+        self.weight.assign(&weight.column(0));
+        self.symmetrize();
+        Ok(())
+    }
+
+    /// Multiplies the inverse correlation matrix by `factor`, keeping the weight vector.
+    ///
+    /// This is a finer-grained remedy for covariance windup than
+    /// [`reset_covariance`](#method.reset_covariance): a factor greater than 1 inflates P and
+    /// makes the filter more adaptive, a factor less than 1 deflates it and makes the filter
+    /// more conservative. As a scalar multiplication, it keeps P symmetric and, for a positive
+    /// factor, positive definite.
+    ///
+    /// **Panics** unless `factor` is positive.
+    pub fn scale_covariance(&mut self, factor: F) {
+        assert!(factor > F::zero(),
+            "covariance scale factor must be positive, got {}", factor);
+        self.inverse_correlation *= factor;
+    }
+
+    /// Applies only the forgetting step P(i) = λ^{-1} P(i-1) to the inverse correlation matrix,
+    /// leaving the weight vector unchanged, e.g. for a time step without a target.
+    ///
+    /// In terms of the equivalent Kalman filter, this is the time update without a measurement
+    /// update, so that the uncertainty of the weights keeps growing across gaps in the targets as
+    /// it would with the same number of updates. A frozen filter is left unchanged.
+    pub fn time_update(&mut self) {
+        if self.frozen {
+            return;
+        }
+        self.inverse_correlation *= self.inv_covariance_forgetting_factor();
+    }
+
+    /// Replaces the weight vector, e.g. with prior knowledge, and if `initialization_factor` is
+    /// `Some(δ)` also resets the inverse correlation matrix to P = δ^{-1} · 𝟙, see
+    /// [`reset_covariance`](#method.reset_covariance). A large δ expresses a high confidence in
+    /// the new weights. The smoothed weight vector, if enabled, restarts at the new weights.
+    ///
+    /// Returns an error without modifying the filter if the length of `weight` does not match the
+    /// number of taps.
+    pub fn set_weight(&mut self, weight: Array1<F>, initialization_factor: Option<F>) -> Result<(), RlsError> {
+        if weight.len() != self.weight.len() {
+            return Err(RlsError::DimensionMismatch {
+                expected: self.weight.len(),
+                got: weight.len(),
+            });
+        }
+        self.weight = weight;
+        self.restart_weight_smoothing();
+        if let Some(initialization_factor) = initialization_factor {
+            self.reset_covariance(initialization_factor);
+        }
+        Ok(())
+    }
+
+    /// Resets the inverse correlation matrix automatically with initialization factor δ, see
+    /// [`reset_covariance`](#method.reset_covariance), whenever its trace drops below
+    /// `threshold` after an update.
+    pub fn reset_covariance_when_trace_below(&mut self, threshold: F, initialization_factor: F) {
+        self.covariance_reset = Some(CovarianceReset {
+            threshold,
+            initialization_factor,
+        });
+    }
+
+    /// Disables the automatic reset of the inverse correlation matrix.
+    pub fn clear_covariance_reset(&mut self) {
+        self.covariance_reset = None;
+    }
+
+    /// Computes the filter output y(i) = w(i) · u(i) without updating the filter state.
+    ///
+    /// With an [intercept](#method.with_bias), the constant 1 is prepended to `input`.
+    ///
+    /// **Panics** if the length of `input` does not match the length of the weight vector.
+    pub fn predict<S>(&self, input: &ArrayBase<S, Ix1>) -> F
+        where S: Data<Elem = F>
+    {
+        assert_eq!(input.len(), self.input_len(),
+            "input of length {} does not match weight of length {}",
+            input.len(), self.input_len());
+        if self.bias {
+            let (intercept, weight) = self.weight.view().split_at(Axis(0), 1);
+            intercept[0] + weight.dot(input)
+        } else {
+            self.weight.dot(input)
+        }
+    }
+
+    /// Computes the filter output for an input given as a slice, see
+    /// [`predict`](#method.predict).
+    pub fn predict_slice(&self, input: &[F]) -> F {
+        self.predict(&aview1(input))
+    }
+
+    /// Computes the filter output for an input given as a view, see [`predict`](#method.predict).
+    ///
+    /// Unlike `predict`, this has no storage type parameter to spell out in generic contexts.
+    pub fn predict_view(&self, input: ArrayView1<F>) -> F {
+        self.predict(&input)
+    }
+
+    /// Computes the filter output for an input given as a two-dimensional patch, flattened in
+    /// row-major order, i.e. the element at `[[r, c]]` is tap `r * ncols + c`.
+    ///
+    /// **Panics** if the number of elements of `patch` does not match the number of taps.
+    pub fn predict_2d<S>(&self, patch: &ArrayBase<S, Ix2>) -> F
+        where S: Data<Elem = F>
+    {
+        assert_eq!(patch.len(), self.input_len(),
+            "patch of {} elements does not match weight of length {}",
+            patch.len(), self.input_len());
+        match patch.as_slice() {
+            Some(input) => self.predict_slice(input),
+            None => self.predict(&Array1::from_iter(patch.iter().cloned())),
+        }
+    }
+
+    /// Computes the posterior error e_post(i) = d(i) - w(i) · u(i) of the last update, i.e. using
+    /// the already updated weight vector.
+    ///
+    /// Called with the `input` and `target` of the last update, this is related to its prior
+    /// error by e_post(i) = e(i) (1 - k(i) · u(i)). Since 1 - k(i) · u(i) =
+    /// λ^{-1} / (λ^{-1} + u(i) · P(i-1) u(i)) lies in (0, 1], |e_post(i)| ≤ |e(i)| (without
+    /// leakage).
+    ///
+    /// **Panics** if the length of `input` does not match the length of the weight vector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate ndarray;
+    /// # extern crate recless;
+    /// use ndarray::arr1;
+    /// use recless::Rls;
+    ///
+    /// # fn main() {
+    /// let mut rls = Rls::<f64>::new(0.1, 0.99, 2);
+    /// for i in 0..100 {
+    ///     let x = i as f64;
+    ///     let input = arr1(&[x.sin(), x.cos()]);
+    ///     let target = 2.0 * input[0] - input[1];
+    ///     let prior_error = rls.update(&input, target);
+    ///     assert!(rls.posteriori_error(&input, target).abs() <= prior_error.abs());
+    /// }
+    /// # }
+    /// ```
+    pub fn posteriori_error<S>(&self, input: &ArrayBase<S, Ix1>, target: F) -> F
+        where S: Data<Elem = F>
+    {
+        target - self.predict(input)
+    }
+
+    /// Computes the filter outputs for each row of `inputs` into `out`, without updating the
+    /// filter state or allocating.
+    ///
+    /// **Panics** if the number of columns of `inputs` does not match the length of the weight
+    /// vector, or if the length of `out` does not match the number of rows of `inputs`.
+    pub fn predict_batch<S>(&self, inputs: &ArrayBase<S, Ix2>, out: &mut Array1<F>)
+        where S: Data<Elem = F>
+    {
+        assert_eq!(inputs.cols(), self.input_len(),
+            "inputs with {} columns do not match weight of length {}",
+            inputs.cols(), self.input_len());
+        assert_eq!(out.len(), inputs.rows(),
+            "output of length {} does not match {} inputs",
+            out.len(), inputs.rows());
+        if self.bias {
+            let (intercept, weight) = self.weight.view().split_at(Axis(0), 1);
+            out.fill(intercept[0]);
+            general_mat_vec_mul(F::one(), inputs, &weight, F::one(), out);
+        } else {
+            general_mat_vec_mul(F::one(), inputs, &self.weight, F::zero(), out);
+        }
+    }
+
+    /// Runs the learned FIR filter over `signal`, returning the output y(i) = w · u(i) for each
+    /// sample x(i), with the tap input vectors u(i) = (x(i), x(i-1), …, x(i-n+1)) as built by a
+    /// [`DelayLine`](struct.DelayLine.html).
+    ///
+    /// The samples before the start of the signal are taken to be zero, so that the first n - 1
+    /// outputs contain the startup transient. With an [intercept](#method.with_bias), it is added
+    /// to each output.
+    pub fn apply<S>(&self, signal: &ArrayBase<S, Ix1>) -> Array1<F>
+        where S: Data<Elem = F>
+    {
+        let start = self.weight.len() - self.input_len();
+        let (intercept, taps) = self.weight.view().split_at(Axis(0), start);
+        let offset = intercept.iter().fold(F::zero(), |sum, &b| sum + b);
+
+        let mut output = Array1::from_elem(signal.len(), offset);
+        for (i, y) in output.iter_mut().enumerate() {
+            for (k, &w) in taps.iter().take(i + 1).enumerate() {
+                *y += w * signal[i - k];
+            }
+        }
+        output
+    }
+
+    /// Forecasts the next `steps` samples of a time series with the filter as an autoregressive
+    /// model, which predicts x(i+1) from u(i) = (x(i), x(i-1), …, x(i-n+1)).
+    ///
+    /// The delay line starts out with the last n samples of `history`, given in chronological
+    /// order, and each prediction is shifted into it as the newest sample for the next one. Since
+    /// the forecasts are fed back in place of the true samples, their errors compound, and the
+    /// forecast is only as good as the model far beyond the first step.
+    ///
+    /// **Panics** if `history` holds fewer samples than there are taps.
+    pub fn forecast<S>(&self, history: &ArrayBase<S, Ix1>, steps: usize) -> Array1<F>
+        where S: Data<Elem = F>
+    {
+        let n = self.input_len();
+        assert!(history.len() >= n,
+            "history of length {} is shorter than the {} taps",
+            history.len(), n);
+
+        let mut delay_line = DelayLine::new(n);
+        for &x in history.iter().skip(history.len() - n) {
+            delay_line.push(x);
+        }
+        let mut forecast = Array1::zeros(steps);
+        for x in forecast.iter_mut() {
+            *x = self.predict(&delay_line.as_array());
+            delay_line.push(*x);
+        }
+        forecast
+    }
+
+    /// Computes the mean squared prediction error over the rows of `inputs` and the
+    /// corresponding elements of `targets`, without updating the filter state.
+    ///
+    /// Returns zero for an empty batch.
+    ///
+    /// **Panics** if the number of columns of `inputs` does not match the length of the weight
+    /// vector, or if the length of `targets` does not match the number of rows of `inputs`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # #[macro_use]
+    /// # extern crate ndarray;
+    /// # extern crate recless;
+    /// use ndarray::{arr1, Array1, Array2};
+    /// use recless::Rls;
+    ///
+    /// # fn main() {
+    /// // A linear system observed through noise of variance 0.01.
+    /// let inputs = Array2::from_shape_fn((400, 2), |(i, j)| ((i * (j + 2)) as f64).sin());
+    /// let noise = Array1::from_shape_fn(400, |i| if i % 2 == 0 { 0.1 } else { -0.1 });
+    /// let targets = inputs.dot(&arr1(&[2.0, -1.0])) + &noise;
+    ///
+    /// let mut rls = Rls::<f64>::new(0.1, 1.0, 2);
+    /// for (input, &target) in inputs.outer_iter().zip(targets.iter()).take(300) {
+    ///     rls.update(&input, target);
+    /// }
+    ///
+    /// let mse = rls.mse(&inputs.slice(s![300.., ..]), &targets.slice(s![300..]));
+    /// assert!((mse - 0.01).abs() < 1e-3);
+    /// assert_eq!(rls.rmse(&inputs.slice(s![300.., ..]), &targets.slice(s![300..])), mse.sqrt());
+    /// # }
+    /// ```
+    pub fn mse<S1, S2>(&self, inputs: &ArrayBase<S1, Ix2>, targets: &ArrayBase<S2, Ix1>) -> F
+        where S1: Data<Elem = F>,
+              S2: Data<Elem = F>,
+    {
+        assert_eq!(inputs.cols(), self.input_len(),
+            "inputs with {} columns do not match weight of length {}",
+            inputs.cols(), self.input_len());
+        assert_eq!(targets.len(), inputs.rows(),
+            "targets of length {} do not match {} inputs",
+            targets.len(), inputs.rows());
+
+        if targets.is_empty() {
+            return F::zero();
+        }
+
+        let sum = inputs.outer_iter()
+            .zip(targets.iter())
+            .fold(F::zero(), |sum, (input, &target)| {
+                let error = target - self.predict(&input);
+                sum + error * error
+            });
+        sum / F::from(targets.len()).unwrap()
+    }
+
+    /// Computes the root mean squared prediction error, see [`mse`](#method.mse).
+    pub fn rmse<S1, S2>(&self, inputs: &ArrayBase<S1, Ix2>, targets: &ArrayBase<S2, Ix1>) -> F
+        where S1: Data<Elem = F>,
+              S2: Data<Elem = F>,
+    {
+        self.mse(inputs, targets).sqrt()
+    }
+
+    /// Returns the trace of the inverse correlation matrix P(i).
+    ///
+    /// The trace shrinks as the filter accumulates information; a very small trace indicates
+    /// that the filter has effectively stopped adapting, see
+    /// [`reset_covariance`](#method.reset_covariance).
+    pub fn covariance_trace(&self) -> F {
+        self.inverse_correlation.diag().scalar_sum()
+    }
+
+    /// Returns an estimate of the condition number of the inverse correlation matrix P(i) as the
+    /// ratio of the largest to the smallest pivot of its Cholesky factorization.
+    ///
+    /// This is an approximate lower bound of the true condition number, costing O(n³). It
+    /// equals the ratio of the largest to the smallest diagonal element for uncorrelated taps
+    /// and stays near 1 for well-conditioned inputs, but grows as the inputs become collinear
+    /// or some directions are not excited. It is infinite if P is not positive definite up to
+    /// round-off.
+    pub fn covariance_condition_estimate(&self) -> F {
+        if self.weight.is_empty() {
+            return F::one();
+        }
+        let factor = match cholesky(&self.inverse_correlation, false) {
+            Some(factor) => factor,
+            None => return F::infinity(),
+        };
+
+        let (max, min) = factor.diag().fold((F::zero(), F::infinity()), |(max, min), &diag| {
+            let pivot = diag * diag;
+            (max.max(pivot), min.min(pivot))
+        });
+        max / min
+    }
+
+    /// Returns the covariance of the weight estimate, σ² · P(i), for the noise variance σ².
+    ///
+    /// This assumes that the residuals of the model are white and Gaussian with variance σ², and
+    /// that the forgetting factor λ is close to 1, so that P(i) is, up to the scale σ², the
+    /// inverse of the correlation matrix of the inputs.
+    pub fn weight_covariance(&self, noise_variance: F) -> Array2<F> {
+        &self.inverse_correlation * noise_variance
+    }
+
+    /// Returns the standard errors of the individual taps, i.e. the square roots of the diagonal
+    /// of the [`weight_covariance`](#method.weight_covariance), under the same assumptions.
+    pub fn weight_std_err(&self, noise_variance: F) -> Array1<F> {
+        self.inverse_correlation.diag().mapv(|p| (p * noise_variance).sqrt())
+    }
+
+    /// Returns the variance σ² · u · P(i) u of the prediction for `input`, for the noise variance
+    /// σ², under the same assumptions as the [`weight_covariance`](#method.weight_covariance).
+    ///
+    /// Together with [`predict`](#method.predict), this gives the predictive distribution of the
+    /// filter output. Inputs unlike those seen so far have a large predictive variance, e.g. to
+    /// flag extrapolation. Negative values due to round-off are clamped to zero.
+    ///
+    /// **Panics** if the length of `input` does not match the length of the weight vector.
+    pub fn predict_variance<S>(&self, input: &ArrayBase<S, Ix1>, noise_variance: F) -> F
+        where S: Data<Elem = F>
+    {
+        (self.quadratic_form(input) * noise_variance).max(F::zero())
+    }
+
+    /// Returns the leave-one-out residual of a sample the filter was last updated with, i.e.
+    /// the error d - w · u the filter would make on the sample had it not been trained on it.
+    ///
+    /// This is the online version of the PRESS residual of least squares,
+    /// e_loo = e_post / (1 - h), with the posterior error e_post = d - w(i) · u and the leverage
+    /// h = u · Φ(i)^{-1} u, where Φ(i) = λ^{-2} P(i)^{-1} is the exponentially weighted
+    /// correlation matrix of the inputs. It costs a matrix-vector product and no refit, and a
+    /// large |e_loo| relative to |e_post| flags an influential sample.
+    ///
+    /// The result is exact for the most recent sample, for which it equals the prior error of
+    /// its update, and for λ = 1 for any sample seen so far. For λ < 1, older samples enter
+    /// Φ(i) downweighted by λ^{i-k}, so their leverage, and hence e_loo, is overestimated.
+    /// The initialization δ^{-1} · 𝟙 of P counts as part of the data.
+    ///
+    /// **Panics** if the length of `input` does not match the length of the weight vector.
+    pub fn loo_residual<S>(&self, input: &ArrayBase<S, Ix1>, target: F) -> F
+        where S: Data<Elem = F>
+    {
+        let posterior_error = target - self.predict(input);
+        let forgetting_factor = self.forgetting_factor();
+        let leverage = forgetting_factor * forgetting_factor * self.quadratic_form(input);
+        posterior_error / (F::one() - leverage)
+    }
+
+    /// Returns the conversion factor γ = 1 - u · k = λ^{-1} / (λ^{-1} + u · P(i) u) of an
+    /// update with `input`, with the gain k the update would compute, without updating the
+    /// filter state.
+    ///
+    /// The conversion factor relates the errors before and after the update as
+    /// e_post = γ e_prior, and thus indicates how much the next step adapts: close to 1, the
+    /// filter already knows the direction of `input` well and barely moves, while close to 0, it
+    /// fits the sample almost exactly. It lies in (0, 1] as long as P(i) is positive
+    /// semidefinite.
+    ///
+    /// **Panics** if the length of `input` does not match the length of the weight vector.
+    pub fn error_variance_gain<S>(&self, input: &ArrayBase<S, Ix1>) -> F
+        where S: Data<Elem = F>
+    {
+        self.inv_forgetting_factor / self.gain_denominator(input)
+    }
+
+    /// Returns the gain denominator c = λ^{-1} + u · P(i) u of an update with `input`, without
+    /// updating the filter state, e.g. for custom step-size rules or diagnostics.
+    ///
+    /// The gain of [`update`](#method.update) is k = P(i) u / c. This uses the current
+    /// forgetting factor, whereas a [variable forgetting factor](#method.set_vff_policy) or a
+    /// [schedule](#method.set_forgetting_schedule) adjusts it before the update, and the
+    /// robust or weighted updates scale λ^{-1} per sample.
+    ///
+    /// **Panics** if the length of `input` does not match the length of the weight vector.
+    pub fn gain_denominator<S>(&self, input: &ArrayBase<S, Ix1>) -> F
+        where S: Data<Elem = F>
+    {
+        self.inv_forgetting_factor + self.quadratic_form(input)
+    }
+
+    /// Returns u · P(i) u, prepending the constant 1 of an intercept to `input`.
+    ///
+    /// **Panics** if the length of `input` does not match the length of the weight vector.
+    fn quadratic_form<S>(&self, input: &ArrayBase<S, Ix1>) -> F
+        where S: Data<Elem = F>
+    {
+        assert_eq!(input.len(), self.input_len(),
+            "input of length {} does not match weight of length {}",
+            input.len(), self.input_len());
+        if self.bias {
+            let mut extended = Array1::from_elem(self.weight.len(), F::one());
+            extended.view_mut().split_at(Axis(0), 1).1.assign(input);
+            extended.dot(&self.inverse_correlation.dot(&extended))
+        } else {
+            input.dot(&self.inverse_correlation.dot(input))
+        }
+    }
+}
+
+impl Rls<f64> {
+
+    /// Converts the filter to single precision, e.g. to deploy a filter trained in double
+    /// precision.
+    ///
+    /// *Note:* All parameters and the state are rounded to the nearest `f32`, so predictions
+    /// differ at the level of single-precision round-off, and an inverse correlation matrix with
+    /// a large condition number may lose its positive definiteness.
+    pub fn to_f32(&self) -> Rls<f32> {
+        self.cast()
+    }
+}
+
+impl Rls<f32> {
+
+    /// Converts the filter to double precision, e.g. to continue training a deployed filter.
+    ///
+    /// The conversion is exact, but does not recover the precision lost in earlier single
+    /// precision updates.
+    pub fn to_f64(&self) -> Rls<f64> {
+        self.cast()
+    }
+}
+
+impl<F: NdFloat> fmt::Display for Rls<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Rls {{ taps: {}, λ: {}, |w|: {}, prior error: {}, tr P: {} }}",
+            self.weight.len(),
+            self.forgetting_factor(),
+            self.weight.dot(&self.weight).sqrt(),
+            self.prior_error,
+            self.inverse_correlation.diag().scalar_sum())
+    }
+}
+
+impl<F: PartialEq> PartialEq for Rls<F> {
+    /// Compares all parameters and the state exactly, ignoring the scratch buffers. For floating
+    /// point comparisons, use [`approx_eq`](#method.approx_eq) instead.
+    fn eq(&self, other: &Self) -> bool {
+        self.initialization_factor == other.initialization_factor
+            && self.inv_forgetting_factor == other.inv_forgetting_factor
+            && self.inv_covariance_forgetting_factor == other.inv_covariance_forgetting_factor
+            && self.gain == other.gain
+            && self.inverse_correlation == other.inverse_correlation
+            && self.weight == other.weight
+            && self.prior_error == other.prior_error
+            && self.vff_policy == other.vff_policy
+            && self.forgetting_schedule == other.forgetting_schedule
+            && self.leakage == other.leakage
+            && self.residual_energy == other.residual_energy
+            && self.residual_weight == other.residual_weight
+            && self.target_sum == other.target_sum
+            && self.target_energy == other.target_energy
+            && self.symmetrize_every == other.symmetrize_every
+            && self.updates_since_symmetrize == other.updates_since_symmetrize
+            && self.covariance_reset == other.covariance_reset
+            && self.frozen == other.frozen
+            && self.min_denominator == other.min_denominator
+            && self.weight_delta_norm == other.weight_delta_norm
+            && self.regularization == other.regularization
+            && self.regularization_tap == other.regularization_tap
+            && self.num_updates == other.num_updates
+            && self.bias == other.bias
+            && self.error_window == other.error_window
+            && self.weight_history == other.weight_history
+            && self.weight_smoothing == other.weight_smoothing
+            && self.input_statistics == other.input_statistics
+    }
+}
+
+impl<F: NdFloat> Default for Rls<F> {
+    /// Constructs an Rls object without taps, with λ = 1 and δ =
+    /// [`DEFAULT_INITIALIZATION_FACTOR`](constant.DEFAULT_INITIALIZATION_FACTOR.html).
+    fn default() -> Self {
+        Rls::new(F::from(DEFAULT_INITIALIZATION_FACTOR).unwrap(), F::one(), 0)
+    }
+}
+
+// Fails to compile if a field ever makes `Rls` lose its thread-safety, see the docs of `Rls`.
+fn _assert_send_sync<F: Send + Sync>() {
+    fn assert<T: Send + Sync>() {}
+    assert::<Rls<F>>();
+}
+
+/// Computes the gain vector k(i) = P(i-1) u(i) / (λ^{-1} + u(i) · P(i-1) u(i)).
+fn update_gain<F, S>(
+    inverse_correlation: &Array2<F>,
+    inv_forgetting_factor: F,
+    input: &ArrayBase<S, Ix1>,
+    gain: &mut Array1<F>,
+)
+    where F: NdFloat,
+          S: Data<Elem = F>,
+{
+    general_mat_vec_mul(
+        F::one(),
+        inverse_correlation,
+        input,
+        F::zero(),
+        gain
+    );
+    let c = inv_forgetting_factor + input.dot(gain);
+
+    *gain /= c;
+}
+
+/// Updates the inverse correlation matrix as P(i) = λ^{-1} (P(i-1) - k(i) u(i)ᵀ P(i-1)), using
+/// the scratch buffer `temp_vec`.
+///
+/// **Panics** with the `blas` feature if `inverse_correlation`, `gain`, or `temp_vec` are not
+/// contiguous in memory.
+fn update_inverse_correlation<F, S>(
+    inverse_correlation: &mut Array2<F>,
+    inv_forgetting_factor: F,
+    input: &ArrayBase<S, Ix1>,
+    gain: &Array1<F>,
+    temp_vec: &mut Array1<F>,
+)
+    where F: NdFloat,
+          S: Data<Elem = F>,
+{
+    general_mat_vec_mul(
+        F::one(),
+        &inverse_correlation.t(),
+        input,
+        F::zero(),
+        temp_vec
+    );
+
+    rank1_update(inverse_correlation, gain, temp_vec);
+    *inverse_correlation *= inv_forgetting_factor;
+}
+
+/// Updates the inverse correlation matrix with directional forgetting as
+/// P(i) = P(i-1) - ε k(i) u(i)ᵀ P(i-1), with ε = 1 - (1 - λ) / (u(i) · P(i-1) u(i)) and the gain
+/// k(i) = P(i-1) u(i) / (λ + u(i) · P(i-1) u(i)), using the scratch buffer `temp_vec`.
+///
+/// **Panics** with the `blas` feature if `inverse_correlation`, `gain`, or `temp_vec` are not
+/// contiguous in memory.
+fn update_inverse_correlation_directional<F, S>(
+    inverse_correlation: &mut Array2<F>,
+    inv_forgetting_factor: F,
+    input: &ArrayBase<S, Ix1>,
+    gain: &Array1<F>,
+    temp_vec: &mut Array1<F>,
+)
+    where F: NdFloat,
+          S: Data<Elem = F>,
+{
+    general_mat_vec_mul(
+        F::one(),
+        &inverse_correlation.t(),
+        input,
+        F::zero(),
+        temp_vec
+    );
+
+    // Without excitation there is neither new nor forgotten information.
+    let r = input.dot(temp_vec);
+    if r <= F::zero() {
+        return;
+    }
+
+    let forgetting_factor = F::one() / inv_forgetting_factor;
+    let epsilon = F::one() - (F::one() - forgetting_factor) / r;
+    *temp_vec *= epsilon;
+
+    rank1_update(inverse_correlation, gain, temp_vec);
+}
+
+#[cfg(feature = "blas")]
+fn same_type<A: 'static, B: 'static>() -> bool {
+    TypeId::of::<A>() == TypeId::of::<B>()
+}
+
+/// Returns `true` if the `blas` feature is enabled and the linked BLAS backend computes a probe
+/// rank-1 update `dger` correctly, so that callers can choose a fallback at startup.
+///
+/// Returns `false` without the `blas` feature, since all linear algebra is then done in pure
+/// Rust. *Note:* A backend that is not linked at all makes the build fail at link time rather
+/// than this probe.
+pub fn blas_available() -> bool {
+    #[cfg(feature = "blas")]
+    {
+        let mut a = arr2(&[[1.0f64]]);
+        rank1_update(&mut a, &arr1(&[2.0]), &arr1(&[3.0]));
+        a[[0, 0]] == -5.0
+    }
+    #[cfg(not(feature = "blas"))]
+    {
+        false
+    }
+}
+
+/// Returns the lower Cholesky factor L of the symmetric matrix A = L Lᵀ, computed from its lower
+/// triangle.
+///
+/// A pivot L_jj² that is NaN or not larger than ε A_jj means that A is not positive definite up
+/// to round-off. If `semidefinite` is `true`, the column of such a pivot is set to zero, which
+/// factors a positive semidefinite A and always succeeds, and otherwise `None` is returned.
+fn cholesky<F: NdFloat>(a: &Array2<F>, semidefinite: bool) -> Option<Array2<F>> {
+    let n = a.rows();
+    let mut factor = Array2::zeros((n, n));
+    for j in 0..n {
+        let diagonal = a[[j, j]];
+        let mut pivot = diagonal;
+        for k in 0..j {
+            pivot -= factor[[j, k]] * factor[[j, k]];
+        }
+        if pivot.is_nan() || pivot <= (F::epsilon() * diagonal).max(F::zero()) {
+            if semidefinite {
+                continue;
+            }
+            return None;
+        }
+        let diag = pivot.sqrt();
+        factor[[j, j]] = diag;
+        for i in j + 1..n {
+            let mut x = a[[i, j]];
+            for k in 0..j {
+                x -= factor[[i, k]] * factor[[j, k]];
+            }
+            factor[[i, j]] = x / diag;
+        }
+    }
+    Some(factor)
+}
+
+/// Solves A X = B in place of `b` for a symmetric positive definite matrix A by its Cholesky
+/// factorization A = L Lᵀ, returning `false` if A is not positive definite up to round-off.
+fn solve_positive_definite<F: NdFloat>(a: &Array2<F>, b: &mut Array2<F>) -> bool {
+    match cholesky(a, false) {
+        Some(factor) => {
+            solve_cholesky(&factor, b);
+            true
+        }
+        None => false,
+    }
+}
+
+/// Solves L Lᵀ X = B in place of `b`, given the lower Cholesky factor L.
+fn solve_cholesky<F: NdFloat>(factor: &Array2<F>, b: &mut Array2<F>) {
+    let n = factor.rows();
+
+    for mut column in b.axis_iter_mut(Axis(1)) {
+        // Forward substitution with L, then back substitution with Lᵀ.
+        forward_substitute(factor, &mut column);
+        for i in (0..n).rev() {
+            let mut x = column[i];
+            for k in i + 1..n {
+                x -= factor[[k, i]] * column[k];
+            }
+            column[i] = x / factor[[i, i]];
+        }
+    }
+}
+
+/// Solves L x = b in place of `b` for a lower triangular matrix L.
+fn forward_substitute<F, S>(factor: &Array2<F>, b: &mut ArrayBase<S, Ix1>)
+    where F: NdFloat,
+          S: DataMut<Elem = F>,
+{
+    for i in 0..factor.rows() {
+        let mut x = b[i];
+        for k in 0..i {
+            x -= factor[[i, k]] * b[k];
+        }
+        b[i] = x / factor[[i, i]];
+    }
+}
+
+/// Computes the eigenvalues and the eigenvectors, as the columns of the returned matrix, of the
+/// symmetric matrix A by cyclic Jacobi rotations.
+fn symmetric_eigen<F: NdFloat>(mut a: Array2<F>) -> (Array1<F>, Array2<F>) {
+    const MAX_SWEEPS: usize = 100;
+
+    let n = a.rows();
+    let mut vectors = Array2::eye(n);
+    let two = F::one() + F::one();
+    let norm = a.iter().fold(F::zero(), |sum, &x| sum + x * x);
+
+    for _ in 0..MAX_SWEEPS {
+        let mut off_diagonal = F::zero();
+        for p in 0..n {
+            for q in p + 1..n {
+                off_diagonal += a[[p, q]] * a[[p, q]];
+            }
+        }
+        if off_diagonal <= F::epsilon() * F::epsilon() * norm {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[[p, q]] == F::zero() {
+                    continue;
+                }
+                // The rotation by the angle φ with t = tan φ annihilating A[p, q].
+                let theta = (a[[q, q]] - a[[p, p]]) / (two * a[[p, q]]);
+                let t = if theta == F::zero() {
+                    F::one()
+                } else {
+                    theta.signum() / (theta.abs() + (theta * theta + F::one()).sqrt())
+                };
+                let cos = F::one() / (t * t + F::one()).sqrt();
+                let sin = t * cos;
+
+                for k in 0..n {
+                    let (x, y) = (a[[k, p]], a[[k, q]]);
+                    a[[k, p]] = cos * x - sin * y;
+                    a[[k, q]] = sin * x + cos * y;
+                }
+                for k in 0..n {
+                    let (x, y) = (a[[p, k]], a[[q, k]]);
+                    a[[p, k]] = cos * x - sin * y;
+                    a[[q, k]] = sin * x + cos * y;
+                }
+                for k in 0..n {
+                    let (x, y) = (vectors[[k, p]], vectors[[k, q]]);
+                    vectors[[k, p]] = cos * x - sin * y;
+                    vectors[[k, q]] = sin * x + cos * y;
+                }
+            }
+        }
+    }
+    (a.diag().to_owned(), vectors)
+}
+
+/// Performs the rank-1 update A ← A - x yᵀ.
+///
+/// *Note:* If the `blas` feature is enabled, uses blas `?ger` for elements of `f32, f64`, and
+/// **panics** if `a`, `x`, or `y` are not contiguous in memory.
+fn rank1_update<F: NdFloat>(a: &mut Array2<F>, x: &Array1<F>, y: &Array1<F>) {
+    #[cfg(feature = "blas")]
+    macro_rules! ger {
+        ($t:ty, $ger:path) => {
+            if same_type::<F, $t>() {
+                let (m, n) = a.dim();
+                let lda = a.strides()[0];
+                let x = x.as_slice().unwrap();
+                let y = y.as_slice().unwrap();
+                let a = a.as_slice_mut().unwrap();
+                unsafe {
+                    $ger(
+                        blas::c::Layout::RowMajor,
+                        m as i32,
+                        n as i32,
+                        -1.0,
+                        slice::from_raw_parts(x.as_ptr() as *const $t, x.len()),
+                        1,
+                        slice::from_raw_parts(y.as_ptr() as *const $t, y.len()),
+                        1,
+                        slice::from_raw_parts_mut(a.as_mut_ptr() as *mut $t, a.len()),
+                        lda as i32,
+                    );
+                }
+                return;
+            }
+        }
+    }
+    #[cfg(feature = "blas")]
+    ger!(f32, blas::c::sger);
+    #[cfg(feature = "blas")]
+    ger!(f64, blas::c::dger);
+
+    for (mut row, &x) in a.outer_iter_mut().zip(x.iter()) {
+        row.scaled_add(-x, y);
+    }
+}
+
+impl<F: NdFloat> Rls<F> {
+
+    /// Performs a recursive update of inverse correlation matrix and weight vector.
+    ///
+    /// Returns the prior error, i.e. the difference between `target` and the filter
+    /// output before the update.
+    ///
+    /// The update works in place on the filter state and the internal scratch buffers and does
+    /// not allocate, which also holds for the other updates with a single sample except for
+    /// [`checked_update`](#method.checked_update), which takes a [snapshot](#method.snapshot)
+    /// of the state it might have to roll back. The exceptions are
+    /// the first update after deserialization, which reallocates the scratch buffers, and the
+    /// [weight history](struct.RlsBuilder.html#method.record_history), which copies the weights
+    /// in each update if enabled.
+    ///
+    /// **Panics** if the update fails, see [`try_update`](#method.try_update).
+    pub fn update<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F) -> F
+        where S: Data<Elem = F>
+    {
+        match self.try_update(input, target) {
+            Ok(prior_error) => prior_error,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Performs a recursive update with an input given as a slice, without copying it, and
+    /// returns the prior error, see [`update`](#method.update).
+    ///
+    /// **Panics** if the length of `input` does not match the number of taps, or if the update
+    /// fails.
+    pub fn update_slice(&mut self, input: &[F], target: F) -> F {
+        assert_eq!(input.len(), self.input_len(),
+            "input of length {} does not match weight of length {}",
+            input.len(), self.input_len());
+        self.update(&aview1(input), target)
+    }
+
+    /// Performs a recursive update with an input given as a view and returns the prior error,
+    /// see [`update`](#method.update).
+    ///
+    /// Unlike `update`, this has no storage type parameter to spell out in generic contexts.
+    ///
+    /// ```
+    /// # extern crate ndarray;
+    /// # extern crate recless;
+    /// use ndarray::arr1;
+    /// use recless::Rls;
+    ///
+    /// # fn main() {
+    /// let mut rls = Rls::new(0.01, 0.99, 2);
+    /// let input = arr1(&[1.0, 2.0]);
+    ///
+    /// assert_eq!(rls.update_view(input.view(), 3.0), 3.0);
+    /// assert_eq!(rls.predict_view(input.view()), rls.predict(&input));
+    /// # }
+    /// ```
+    pub fn update_view(&mut self, input: ArrayView1<F>, target: F) -> F {
+        self.update(&input, target)
+    }
+
+    /// Performs a recursive update with an input given as a two-dimensional patch, e.g. for
+    /// spatio-temporal filters, and returns the prior error, see [`update`](#method.update).
+    ///
+    /// The patch is flattened in row-major order, i.e. the element at `[[r, c]]` is tap
+    /// `r * ncols + c`. It is only copied if it is not contiguous in standard layout.
+    ///
+    /// **Panics** if the number of elements of `patch` does not match the number of taps, or if
+    /// the update fails.
+    pub fn update_2d<S>(&mut self, patch: &ArrayBase<S, Ix2>, target: F) -> F
+        where S: Data<Elem = F>
+    {
+        assert_eq!(patch.len(), self.input_len(),
+            "patch of {} elements does not match weight of length {}",
+            patch.len(), self.input_len());
+        match patch.as_slice() {
+            Some(input) => self.update_slice(input, target),
+            None => self.update(&Array1::from_iter(patch.iter().cloned()), target),
+        }
+    }
+
+    /// Performs a recursive update of inverse correlation matrix and weight vector,
+    /// returning the prior error.
+    ///
+    /// If the filter is [frozen](#method.freeze), only the prior error is calculated.
+    ///
+    /// Returns an error without modifying the filter if the length of `input` does not
+    /// match the number of taps, if the internal buffers are not contiguous in memory, or if
+    /// the gain denominator is below the [minimum](#method.set_min_denominator).
+    pub fn try_update<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F) -> Result<F, RlsError>
+        where S: Data<Elem = F>
+    {
+        self.try_update_impl(input, target, UpdateKind::Standard)
+    }
+
+    /// Performs a recursive update like [`try_update`](#method.try_update), but rolls the filter
+    /// back to its state before the update if it produced a non-finite prior error, gain,
+    /// weight, or inverse correlation matrix, e.g. because of a NaN in `input`.
+    ///
+    /// Returns [`RlsError::NonFinite`] in that case, so that a single bad sample does not
+    /// permanently corrupt the filter. The rollback requires a [snapshot](#method.snapshot) of
+    /// the state mutated by the update, i.e. a copy of the weights and the inverse correlation
+    /// matrix, per update.
+    ///
+    /// [`RlsError::NonFinite`]: enum.RlsError.html#variant.NonFinite
+    pub fn checked_update<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F) -> Result<F, RlsError>
+        where S: Data<Elem = F>
+    {
+        let snapshot = self.snapshot();
+
+        let prior_error = self.try_update(input, target)?;
+
+        let finite = prior_error.is_finite()
+            && self.gain.iter().all(|x| x.is_finite())
+            && self.weight.iter().all(|x| x.is_finite())
+            && self.inverse_correlation.iter().all(|x| x.is_finite());
+        if !finite {
+            self.restore(snapshot);
+            return Err(RlsError::NonFinite);
+        }
+
+        Ok(prior_error)
+    }
+
+    /// Performs a recursive update like [`update`](#method.update), but with the sample entering
+    /// the least squares problem with the observation weight q, returning the prior error.
+    ///
+    /// The gain becomes k(i) = P(i-1) u(i) / (λ^{-1} / q + u(i) · P(i-1) u(i)), which is used as
+    /// in the standard update of the weight vector and the inverse correlation matrix, so that the
+    /// correlation matrix grows by q u(i) u(i)ᵀ instead of u(i) u(i)ᵀ. With q = 1 this is exactly
+    /// the standard update, while q = 0 ignores the sample apart from forgetting.
+    ///
+    /// **Panics** if q is negative or if the update fails, see [`try_update`](#method.try_update).
+    pub fn update_weighted<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F, weight: F) -> F
+        where S: Data<Elem = F>
+    {
+        assert!(weight >= F::zero(),
+            "observation weight must be non-negative, got {}", weight);
+        match self.try_update_impl(input, target, UpdateKind::Weighted(weight)) {
+            Ok(prior_error) => prior_error,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Performs a recursive update like [`update`](#method.update), but downweights the sample
+    /// by the Huber weight q = min(1, δ_H / |e|) of its prior error e, returning the prior error.
+    ///
+    /// The sample enters the least squares problem with weight q, so that the gain becomes
+    /// k(i) = P(i-1) u(i) / (λ^{-1} / q + u(i) · P(i-1) u(i)). For |e| ≤ δ_H this is exactly the
+    /// standard update, while the weight correction of an outlier is bounded by about δ_H.
+    ///
+    /// **Panics** if δ_H is not positive or if the update fails, see
+    /// [`try_update`](#method.try_update).
+    pub fn update_robust<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F, huber_delta: F) -> F
+        where S: Data<Elem = F>
+    {
+        assert!(huber_delta > F::zero(),
+            "Huber threshold must be positive, got {}", huber_delta);
+        match self.try_update_robust(input, target, huber_delta) {
+            Ok(prior_error) => prior_error,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Performs a recursive update like [`update_robust`](#method.update_robust), returning the
+    /// prior error.
+    ///
+    /// Returns [`RlsError::InvalidHuberDelta`] without modifying the filter if δ_H is not
+    /// positive, and otherwise fails like [`try_update`](#method.try_update).
+    ///
+    /// [`RlsError::InvalidHuberDelta`]: enum.RlsError.html#variant.InvalidHuberDelta
+    pub fn try_update_robust<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F, huber_delta: F)
+        -> Result<F, RlsError>
+        where S: Data<Elem = F>
+    {
+        if huber_delta.is_nan() || huber_delta <= F::zero() {
+            return Err(RlsError::InvalidHuberDelta);
+        }
+        self.try_update_impl(input, target, UpdateKind::Huber(huber_delta))
+    }
+
+    /// Updates the filter with the rows of `inputs` and the corresponding elements of `targets`
+    /// at once, leaving the weight vector and the inverse correlation matrix equal up to
+    /// round-off to calling [`update`](#method.update) for each row in order.
+    ///
+    /// With U the b × n matrix of inputs and d the targets of a block of b samples, forgetting is
+    /// applied within the block in order, so that the sample j = 1, …, b enters with the weight
+    /// λ^{b-j}. By the Woodbury identity, with the diagonal matrix D^{-1} = diag(λ^{j-2}) and
+    /// S = D^{-1} + U P(i-b) Uᵀ, the block correction
+    ///
+    /// w(i) = w(i-b) + P(i-b) Uᵀ S^{-1} (d - U w(i-b)),
+    /// P(i) = λ^{-b} (P(i-b) - P(i-b) Uᵀ S^{-1} U P(i-b))
+    ///
+    /// is applied once, solving with S by a Cholesky factorization. This replaces b rank-1
+    /// updates by matrix products, which pays off for blocks that are small compared to n.
+    ///
+    /// The prior errors of the sequential updates follow from the Cholesky factor of S, so the
+    /// prior error of the last row, the [residual variance](#method.residual_variance), and
+    /// [R²](#method.r_squared) match as well. The gain vector is not updated,
+    /// [`last_weight_delta_norm`](#method.last_weight_delta_norm) reports the change of the
+    /// weights over the whole block, and an automatic covariance reset is only checked at the end
+    /// of the block. A frozen filter, and filters with a variable forgetting factor, leakage,
+    /// regularization, or an error window, which act on each sample, are updated row by row
+    /// instead.
+    ///
+    /// **Panics** if the number of columns of `inputs` does not match the length of the weight
+    /// vector, if the length of `targets` does not match the number of rows of `inputs`, or if
+    /// S is not positive definite.
+    pub fn update_block<S1, S2>(&mut self, inputs: &ArrayBase<S1, Ix2>, targets: &ArrayBase<S2, Ix1>)
+        where S1: Data<Elem = F>,
+              S2: Data<Elem = F>,
+    {
+        assert_eq!(inputs.cols(), self.input_len(),
+            "inputs with {} columns do not match weight of length {}",
+            inputs.cols(), self.input_len());
+        assert_eq!(targets.len(), inputs.rows(),
+            "targets of length {} do not match {} inputs",
+            targets.len(), inputs.rows());
+
+        let b = inputs.rows();
+        if b == 0 {
+            return;
+        }
+
+        if self.frozen
+            || self.vff_policy.is_some()
+            || self.forgetting_schedule.is_some()
+            || self.inv_covariance_forgetting_factor.is_some()
+            || self.min_denominator != F::zero()
+            || self.leakage != F::zero()
+            || self.regularization != F::zero()
+            || self.error_window.is_some()
+            || self.weight_history.is_some()
+            || self.weight_smoothing.is_some()
+        {
+            for (input, &target) in inputs.outer_iter().zip(targets.iter()) {
+                self.update(&input, target);
+            }
+            return;
+        }
+
+        let n = self.weight.len();
+        let inputs = if self.bias {
+            let mut full = Array2::from_elem([b, n], F::one());
+            full.view_mut().split_at(Axis(1), 1).1.assign(inputs);
+            full
+        } else {
+            inputs.to_owned()
+        };
+
+        // S = D^{-1} + U P Uᵀ, with D^{-1}_jj = λ^{j-2} for j = 1, …, b.
+        let p_ut = self.inverse_correlation.dot(&inputs.t());
+        let mut s = inputs.dot(&p_ut);
+        let forgetting_factor = F::one() / self.inv_forgetting_factor;
+        let mut d_inv = self.inv_forgetting_factor;
+        for j in 0..b {
+            s[[j, j]] += d_inv;
+            d_inv *= forgetting_factor;
+        }
+
+        // Solve S [Y y] = [U P  d - U w] for both corrections at once.
+        let mut rhs = Array2::zeros([b, n + 1]);
+        {
+            let (mut left, mut right) = rhs.view_mut().split_at(Axis(1), n);
+            left.assign(&inputs.dot(&self.inverse_correlation));
+            let mut residual = right.column_mut(0);
+            residual.assign(targets);
+            general_mat_vec_mul(-F::one(), &inputs, &self.weight, F::one(), &mut residual);
+        }
+        let factor = match cholesky(&s, false) {
+            Some(factor) => factor,
+            None => panic!("block update matrix is not positive definite"),
+        };
+
+        // With L z = d - U w(i-b) for the Cholesky factor L of S, the prior error of the j-th
+        // sequential update is L_jj z_j, with variance L_jj², and its posterior error is
+        // D^{-1}_jj z_j / L_jj.
+        let mut innovations = rhs.column(n).to_owned();
+        forward_substitute(&factor, &mut innovations);
+        let mut d_inv = self.inv_forgetting_factor;
+        for (j, (&z, &target)) in innovations.iter().zip(targets.iter()).enumerate() {
+            let posterior_error = d_inv * z / factor[[j, j]];
+            self.residual_energy = forgetting_factor * self.residual_energy + posterior_error * posterior_error;
+            self.residual_weight = forgetting_factor * self.residual_weight + F::one();
+            self.target_sum = forgetting_factor * self.target_sum + target;
+            self.target_energy = forgetting_factor * self.target_energy + target * target;
+            d_inv *= forgetting_factor;
+        }
+        self.prior_error = factor[[b - 1, b - 1]] * innovations[b - 1];
+
+        solve_cholesky(&factor, &mut rhs);
+
+        let (y, step) = rhs.view().split_at(Axis(1), n);
+        let delta = p_ut.dot(&step.column(0));
+        self.weight += &delta;
+        self.weight_delta_norm = delta.dot(&delta).sqrt();
+
+        self.inverse_correlation -= &p_ut.dot(&y);
+        self.inverse_correlation *= self.inv_forgetting_factor.powi(b as i32);
+
+        if self.symmetrize_every > 0 {
+            self.updates_since_symmetrize += b;
+            if self.updates_since_symmetrize >= self.symmetrize_every {
+                self.symmetrize();
+            }
+        }
+
+        if let Some(reset) = self.covariance_reset {
+            if self.covariance_trace() < reset.threshold {
+                self.reset_covariance(reset.initialization_factor);
+            }
+        }
+
+        self.num_updates += b as u64;
+        for input in inputs.outer_iter() {
+            self.input_statistics.observe(&input);
+        }
+    }
+
+    /// Performs a recursive update, see [`update`](#method.update), returning a summary of the
+    /// step for logging or tuning.
+    ///
+    /// **Panics** if the update fails, see [`try_update`](#method.try_update).
+    pub fn update_reporting<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F) -> StepReport<F>
+        where S: Data<Elem = F>
+    {
+        let prior_error = self.update(input, target);
+        StepReport {
+            prior_error,
+            gain_norm: self.gain.dot(&self.gain).sqrt(),
+            weight_delta_norm: self.weight_delta_norm,
+            covariance_trace: self.covariance_trace(),
+        }
+    }
+
+    /// Performs a recursive update with directional forgetting, returning the prior error.
+    ///
+    /// Exponential forgetting discounts the information in all directions, so that P(i) grows
+    /// without bound in directions the inputs do not excite (estimator windup). Directional
+    /// forgetting (Kulhavý) instead only forgets the information along the current input u(i):
+    /// with r = u(i) · P(i-1) u(i), the correlation matrix is updated as
+    /// R(i) = R(i-1) + ε u(i) u(i)ᵀ with ε = 1 - (1 - λ) / r, i.e.
+    /// P(i) = P(i-1) - P(i-1) u(i) u(i)ᵀ P(i-1) / (ε^{-1} + r), and the gain becomes
+    /// k(i) = P(i-1) u(i) / (λ + r). Along u(i), information is forgotten with λ as before.
+    ///
+    /// **Panics** if the update fails, see [`try_update`](#method.try_update).
+    pub fn update_directional<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F) -> F
+        where S: Data<Elem = F>
+    {
+        match self.try_update_impl(input, target, UpdateKind::Directional) {
+            Ok(prior_error) => prior_error,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Converts all parameters and the state element-wise to `G`, reallocating the scratch
+    /// buffers.
+    fn cast<G: NdFloat>(&self) -> Rls<G> {
+        let cast = |x: F| G::from(x).unwrap();
+
+        Rls {
+            initialization_factor: cast(self.initialization_factor),
+            inv_forgetting_factor: cast(self.inv_forgetting_factor),
+            inv_covariance_forgetting_factor: self.inv_covariance_forgetting_factor.map(cast),
+            gain: self.gain.mapv(cast),
+            inverse_correlation: self.inverse_correlation.mapv(cast),
+            weight: self.weight.mapv(cast),
+            prior_error: cast(self.prior_error),
+            temp_vec: Array1::zeros(self.weight.len()),
+            vff_policy: self.vff_policy.map(|policy| VffPolicy {
+                min_forgetting_factor: cast(policy.min_forgetting_factor),
+                max_forgetting_factor: cast(policy.max_forgetting_factor),
+                sensitivity: cast(policy.sensitivity),
+            }),
+            forgetting_schedule: self.forgetting_schedule.map(|schedule| ForgettingSchedule {
+                initial_forgetting_factor: cast(schedule.initial_forgetting_factor),
+                final_forgetting_factor: cast(schedule.final_forgetting_factor),
+                rate: cast(schedule.rate),
+            }),
+            leakage: cast(self.leakage),
+            residual_energy: cast(self.residual_energy),
+            residual_weight: cast(self.residual_weight),
+            target_sum: cast(self.target_sum),
+            target_energy: cast(self.target_energy),
+            symmetrize_every: self.symmetrize_every,
+            updates_since_symmetrize: self.updates_since_symmetrize,
+            covariance_reset: self.covariance_reset.map(|reset| CovarianceReset {
+                threshold: cast(reset.threshold),
+                initialization_factor: cast(reset.initialization_factor),
+            }),
+            frozen: self.frozen,
+            min_denominator: cast(self.min_denominator),
+            weight_delta_norm: cast(self.weight_delta_norm),
+            regularization: cast(self.regularization),
+            regularization_tap: self.regularization_tap,
+            num_updates: self.num_updates,
+            bias: self.bias,
+            bias_input: Array1::zeros(self.bias_input.len()),
+            error_window: self.error_window.as_ref().map(|window| ErrorWindow {
+                errors: window.errors.mapv(cast),
+                next: window.next,
+                len: window.len,
+            }),
+            weight_history: self.weight_history.as_ref().map(|history| {
+                history.iter().map(|weight| weight.mapv(cast)).collect()
+            }),
+            weight_smoothing: self.weight_smoothing.as_ref().map(|smoothing| WeightSmoothing {
+                alpha: cast(smoothing.alpha),
+                weight: smoothing.weight.mapv(cast),
+            }),
+            input_statistics: self.input_statistics.cast(),
+        }
+    }
+
+    /// Performs a recursive update with the sign-error weight correction
+    /// w(i) = w(i-1) + sgn(e(i)) k(i), returning the prior error.
+    ///
+    /// The gain and the inverse correlation matrix are updated exactly as in
+    /// [`update`](#method.update), but the weight correction replaces the multiplication by the
+    /// prior error with its sign. This is cheaper in fixed-point arithmetic and bounds the effect
+    /// of outliers, at the cost of slower convergence and a larger misadjustment, since the
+    /// step no longer shrinks with the error. It does not minimize the least squares cost.
+    ///
+    /// **Panics** if the update fails, see [`try_update`](#method.try_update).
+    pub fn update_sign_error<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F) -> F
+        where S: Data<Elem = F>
+    {
+        match self.try_update_impl(input, target, UpdateKind::SignError) {
+            Ok(prior_error) => prior_error,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Performs a recursive update followed by a group-wise soft-thresholding of the weight
+    /// vector, returning the prior error.
+    ///
+    /// After the standard [`update`](#method.update), the weights w_g of each group g, given as a
+    /// range of indices into the weight vector, are shrunk towards zero by the proximal operator
+    /// of the L2,1 norm,
+    ///
+    /// w_g ← max(0, 1 - τ / |w_g|) · w_g,
+    ///
+    /// with τ the threshold `lambda_l21`. Groups whose weights have a norm of at most τ are set
+    /// to exactly zero, which drives irrelevant groups of features out of the model. With an
+    /// [intercept](#method.with_bias), the indices include it at index 0.
+    ///
+    /// *Note:* This is a heuristic, an RLS step followed by a proximal step, and not the joint
+    /// minimizer of the least squares cost with an L2,1 penalty. The inverse correlation matrix
+    /// is not aware of the shrinkage, and the shrinkage in every update biases the remaining
+    /// groups towards zero, the more so the smaller the gain.
+    ///
+    /// **Panics** if a group extends beyond the weight vector, if `lambda_l21` is negative, or if
+    /// the update fails, see [`try_update`](#method.try_update).
+    pub fn update_group_sparse<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F, groups: &[Range<usize>], lambda_l21: F)
+        -> F
+        where S: Data<Elem = F>
+    {
+        assert!(lambda_l21 >= F::zero(),
+            "threshold must be non-negative, got {}", lambda_l21);
+        for group in groups {
+            assert!(group.start <= group.end && group.end <= self.weight.len(),
+                "group {:?} out of range for weight of length {}", group, self.weight.len());
+        }
+
+        let prior_error = self.update(input, target);
+        if self.frozen {
+            return prior_error;
+        }
+
+        for group in groups {
+            let mut weight = self.weight.view_mut().split_at(Axis(0), group.end).0
+                .split_at(Axis(0), group.start).1;
+            let norm = weight.dot(&weight).sqrt();
+            if norm <= lambda_l21 {
+                weight.fill(F::zero());
+            } else {
+                weight *= F::one() - lambda_l21 / norm;
+            }
+        }
+        prior_error
+    }
+
+    fn try_update_impl<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F, kind: UpdateKind<F>)
+        -> Result<F, RlsError>
+        where S: Data<Elem = F>
+    {
+        if !self.bias {
+            return self.try_update_taps(input, target, kind);
+        }
+
+        if input.len() != self.input_len() {
+            return Err(RlsError::DimensionMismatch {
+                expected: self.input_len(),
+                got: input.len(),
+            });
+        }
+
+        // The scratch vector is empty after deserialization.
+        let mut bias_input = mem::replace(&mut self.bias_input, Array1::zeros(0));
+        if bias_input.len() != self.weight.len() {
+            bias_input = Array1::zeros(self.weight.len());
+        }
+        bias_input[0] = F::one();
+        for (x, &u) in bias_input.iter_mut().skip(1).zip(input.iter()) {
+            *x = u;
+        }
+
+        let result = self.try_update_taps(&bias_input, target, kind);
+        self.bias_input = bias_input;
+        result
+    }
+
+    /// Performs the update with an input of the full length of the weight vector.
+    fn try_update_taps<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F, kind: UpdateKind<F>)
+        -> Result<F, RlsError>
+        where S: Data<Elem = F>
+    {
+        if input.len() != self.weight.len() {
+            return Err(RlsError::DimensionMismatch {
+                expected: self.weight.len(),
+                got: input.len(),
+            });
+        }
+
+        if self.frozen {
+            self.prior_error = target - self.weight.dot(input);
+            self.record_prior_error();
+            return Ok(self.prior_error);
+        }
+
+        // The scratch vector is empty after deserialization.
+        if self.temp_vec.len() != self.weight.len() {
+            self.temp_vec = Array1::zeros(self.weight.len());
+        }
+
+        if self.inverse_correlation.as_slice().is_none()
+            || self.gain.as_slice().is_none()
+            || self.temp_vec.as_slice().is_none()
+        {
+            return Err(RlsError::NonContiguous);
+        }
+
+        // Restored if the update is skipped because of a small gain denominator.
+        let previous_prior_error = self.prior_error;
+        let previous_inv_forgetting_factor = self.inv_forgetting_factor;
+
+        if let Some(schedule) = self.forgetting_schedule {
+            self.inv_forgetting_factor = F::one() / schedule.forgetting_factor(self.num_updates);
+        }
+
+        if let Some(policy) = self.vff_policy {
+            let prior_error = target - self.weight.dot(input);
+            general_mat_vec_mul(
+                F::one(),
+                &self.inverse_correlation,
+                input,
+                F::zero(),
+                &mut self.temp_vec
+            );
+            let normalized_squared_error = prior_error * prior_error / (F::one() + input.dot(&self.temp_vec));
+            self.inv_forgetting_factor = F::one() / policy.forgetting_factor(normalized_squared_error);
+        }
+
+        // Calculate the prior error using the not yet updated tap weight.
+        self.prior_error = target - self.weight.dot(input);
+
+        // An outlier enters with the Huber weight q < 1, i.e. as if λ^{-1} were λ^{-1} / q, and a
+        // weighted sample likewise with its observation weight q.
+        // Directional forgetting uses the gain P(i-1) u(i) / (λ + u(i) · P(i-1) u(i)).
+        let gain_inv_forgetting_factor = match kind {
+            UpdateKind::Huber(huber_delta) if self.prior_error.abs() > huber_delta => {
+                self.inv_forgetting_factor * self.prior_error.abs() / huber_delta
+            }
+            UpdateKind::Directional => F::one() / self.inv_forgetting_factor,
+            UpdateKind::Weighted(weight) => self.inv_forgetting_factor / weight,
+            _ => self.inv_forgetting_factor,
+        };
+
+        if self.min_denominator > F::zero() {
+            general_mat_vec_mul(
+                F::one(),
+                &self.inverse_correlation,
+                input,
+                F::zero(),
+                &mut self.temp_vec
+            );
+            let denominator = gain_inv_forgetting_factor + input.dot(&self.temp_vec);
+            if denominator.abs() < self.min_denominator {
+                self.prior_error = previous_prior_error;
+                self.inv_forgetting_factor = previous_inv_forgetting_factor;
+                return Err(RlsError::SmallDenominator);
+            }
+        }
+
+        update_gain(
+            &self.inverse_correlation,
+            gain_inv_forgetting_factor,
+            input,
+            &mut self.gain,
+        );
+
+        // Update the tap weight, with the sign-error variant correcting by sgn(e(i)) k(i).
+        let correction = match kind {
+            UpdateKind::SignError if self.prior_error > F::zero() => F::one(),
+            UpdateKind::SignError if self.prior_error < F::zero() => -F::one(),
+            UpdateKind::SignError => F::zero(),
+            _ => self.prior_error,
+        };
+        if self.leakage != F::zero() {
+            self.weight *= F::one() - self.leakage / self.inv_forgetting_factor;
+        }
+        self.weight.scaled_add(correction, &self.gain);
+        self.weight_delta_norm = correction.abs() * self.gain.dot(&self.gain).sqrt();
+
+        // Accumulate the posterior error using the updated tap weight.
+        let posterior_error = target - self.weight.dot(input);
+        let forgetting_factor = F::one() / self.inv_forgetting_factor;
+        self.residual_energy = forgetting_factor * self.residual_energy + posterior_error * posterior_error;
+        self.residual_weight = forgetting_factor * self.residual_weight + F::one();
+        self.target_sum = forgetting_factor * self.target_sum + target;
+        self.target_energy = forgetting_factor * self.target_energy + target * target;
+
+        // The contiguity of the buffers passed to BLAS, if enabled, was checked above.
+        match kind {
+            UpdateKind::Directional => {
+                update_inverse_correlation_directional(
+                    &mut self.inverse_correlation,
+                    self.inv_forgetting_factor,
+                    input,
+                    &self.gain,
+                    &mut self.temp_vec,
+                );
+            }
+            _ => {
+                let inv_covariance_forgetting_factor = self.inv_covariance_forgetting_factor();
+                update_inverse_correlation(
+                    &mut self.inverse_correlation,
+                    inv_covariance_forgetting_factor,
+                    input,
+                    &self.gain,
+                    &mut self.temp_vec,
+                );
+            }
+        }
+
+        if self.regularization != F::zero() {
+            self.regularize();
+        }
+
+        if self.symmetrize_every > 0 {
+            self.updates_since_symmetrize += 1;
+            if self.updates_since_symmetrize >= self.symmetrize_every {
+                self.symmetrize();
+            }
+        }
+
+        if let Some(reset) = self.covariance_reset {
+            if self.covariance_trace() < reset.threshold {
+                self.reset_covariance(reset.initialization_factor);
+            }
+        }
+
+        self.num_updates += 1;
+        self.input_statistics.observe(input);
+        self.record_prior_error();
+        if let Some(ref mut history) = self.weight_history {
+            history.push(self.weight.clone());
+        }
+        if let Some(ref mut smoothing) = self.weight_smoothing {
+            smoothing.weight *= F::one() - smoothing.alpha;
+            smoothing.weight.scaled_add(smoothing.alpha, &self.weight);
+        }
+
+        Ok(self.prior_error)
+    }
+
+    /// Stores the prior error in the error window, if enabled.
+    fn record_prior_error(&mut self) {
+        if let Some(ref mut window) = self.error_window {
+            window.push(self.prior_error);
+        }
+    }
+
+    /// Adds the regularization n (1 - λ) μ e_j e_jᵀ of the next tap j to the correlation matrix,
+    /// as a sample with input (n (1 - λ) μ)^{1/2} e_j and target 0 without forgetting.
+    fn regularize(&mut self) {
+        let n = self.weight.len();
+        if n == 0 {
+            return;
+        }
+        let j = self.regularization_tap;
+        self.regularization_tap = (j + 1) % n;
+
+        let forgetting_factor = F::one() / self.inv_forgetting_factor;
+        let sample_weight = F::from(n).unwrap() * (F::one() - forgetting_factor) * self.regularization;
+        if sample_weight <= F::zero() {
+            return;
+        }
+
+        // P e_j is the j-th column, and the denominator is (n (1 - λ) μ)^{-1} + P_jj.
+        self.temp_vec.assign(&self.inverse_correlation.column(j));
+        let c = F::one() / sample_weight + self.temp_vec[j];
+
+        let weight_j = self.weight[j];
+        self.weight.scaled_add(-weight_j / c, &self.temp_vec);
+
+        for (mut row, &p) in self.inverse_correlation.outer_iter_mut().zip(self.temp_vec.iter()) {
+            row.scaled_add(-p / c, &self.temp_vec);
+        }
+    }
+
+    /// Performs a recursive update for each row of `inputs` and the corresponding
+    /// element of `targets`, in order.
+    ///
+    /// Returns the prior errors of all steps.
+    ///
+    /// **Panics** if the number of rows of `inputs` does not match the length of
+    /// `targets`, or if the number of columns of `inputs` does not match the number of
+    /// taps.
+    pub fn update_batch<S1, S2>(&mut self, inputs: &ArrayBase<S1, Ix2>, targets: &ArrayBase<S2, Ix1>) -> Array1<F>
+        where S1: Data<Elem = F>,
+              S2: Data<Elem = F>,
+    {
+        assert_eq!(inputs.rows(), targets.len(),
+            "{} input rows do not match {} targets",
+            inputs.rows(), targets.len());
+        assert_eq!(inputs.cols(), self.input_len(),
+            "{} input columns do not match weight of length {}",
+            inputs.cols(), self.input_len());
+
+        let mut prior_errors = Array1::zeros(targets.len());
+        for ((input, &target), prior_error) in inputs.outer_iter().zip(targets.iter()).zip(prior_errors.iter_mut()) {
+            *prior_error = self.update(&input, target);
+        }
+        prior_errors
+    }
+
+    /// Performs a recursive update for each `(input, target)` pair of `samples`, in
+    /// order.
+    ///
+    /// Returns the mean squared prior error over all samples, or zero if `samples` is
+    /// empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate ndarray;
+    /// # extern crate recless;
+    /// use ndarray::arr1;
+    /// use recless::Rls;
+    ///
+    /// # fn main() {
+    /// let samples = vec![
+    ///     (arr1(&[1.0, 0.0]), 2.0),
+    ///     (arr1(&[0.0, 1.0]), -1.0),
+    ///     (arr1(&[1.0, 1.0]), 1.0),
+    /// ];
+    /// let mut rls = Rls::<f64>::new(1e-6, 1.0, 2);
+    /// rls.fit(samples);
+    /// assert!((rls.predict(&arr1(&[1.0, 0.0])) - 2.0).abs() < 1e-4);
+    /// # }
+    /// ```
+    pub fn fit<I, S>(&mut self, samples: I) -> F
+        where I: IntoIterator<Item = (ArrayBase<S, Ix1>, F)>,
+              S: Data<Elem = F>,
+    {
+        let mut sum_squared_error = F::zero();
+        let mut n = 0;
+        for (input, target) in samples {
+            let prior_error = self.update(&input, target);
+            sum_squared_error += prior_error * prior_error;
+            n += 1;
+        }
+
+        if n > 0 {
+            sum_squared_error / F::from(n).unwrap()
+        } else {
+            F::zero()
+        }
+    }
+
+    /// Selects the forgetting factor among `candidates` with the smallest mean squared prior
+    /// error on a validation batch, returning it together with that error.
+    ///
+    /// For each candidate λ, a new filter with initialization factor δ and as many taps as
+    /// `inputs` has columns is fit to the rows of `inputs` and `targets` in order, as with
+    /// [`update_batch`](#method.update_batch). The prior errors measure how well each filter
+    /// predicts the next target, so too large a λ is penalized for tracking changes slowly and
+    /// too small a λ for its noisy weights. Ties go to the earlier candidate.
+    ///
+    /// **Panics** if `candidates` is empty, if a candidate does not satisfy 0 < λ ≤ 1, or if the
+    /// length of `targets` does not match the number of rows of `inputs`.
+    pub fn tune_forgetting_factor<S1, S2>(
+        inputs: &ArrayBase<S1, Ix2>,
+        targets: &ArrayBase<S2, Ix1>,
+        candidates: &[F],
+        initialization_factor: F,
+    ) -> (F, F)
+        where S1: Data<Elem = F>,
+              S2: Data<Elem = F>,
+    {
+        assert!(!candidates.is_empty(), "no candidate forgetting factors");
+
+        let evaluate = |forgetting_factor: F| {
+            assert!(forgetting_factor > F::zero() && forgetting_factor <= F::one(),
+                "forgetting factor must satisfy 0 < λ <= 1, got {}", forgetting_factor);
+            let mut rls = Rls::new(initialization_factor, forgetting_factor, inputs.cols());
+            let prior_errors = rls.update_batch(inputs, targets);
+            if prior_errors.is_empty() {
+                F::zero()
+            } else {
+                prior_errors.dot(&prior_errors) / F::from(prior_errors.len()).unwrap()
+            }
+        };
+
+        let mut best = (candidates[0], evaluate(candidates[0]));
+        for &forgetting_factor in &candidates[1..] {
+            let mse = evaluate(forgetting_factor);
+            if mse < best.1 {
+                best = (forgetting_factor, mse);
+            }
+        }
+        best
+    }
+
+    /// Returns an iterator which, for each `(input, target)` pair of `samples`, yields the a
+    /// priori prediction w(i-1) · u(i) and then updates the filter with the pair.
+    ///
+    /// The iterator is lazy, so samples are only consumed as predictions are requested, e.g.
+    /// to compute a learning curve with `map` and `take`.
+    ///
+    /// **Panics** while iterating if an update fails, see [`update`](#method.update).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # extern crate ndarray;
+    /// # extern crate recless;
+    /// use ndarray::arr1;
+    /// use recless::Rls;
+    ///
+    /// # fn main() {
+    /// let samples = vec![
+    ///     (arr1(&[1.0, 0.0]), 2.0),
+    ///     (arr1(&[0.0, 1.0]), -1.0),
+    ///     (arr1(&[1.0, 1.0]), 1.0),
+    /// ];
+    /// let mut rls = Rls::<f64>::new(1e-6, 1.0, 2);
+    /// let predictions: Vec<f64> = rls.predictions(samples).collect();
+    ///
+    /// // The first prediction is made with the initial zero weights, and the last one with the
+    /// // weights fitted to the first two samples.
+    /// assert_eq!(predictions.len(), 3);
+    /// assert_eq!(predictions[0], 0.0);
+    /// assert!((predictions[2] - 1.0).abs() < 1e-4);
+    /// # }
+    /// ```
+    pub fn predictions<I, S>(&mut self, samples: I) -> PredictIter<'_, F, I::IntoIter>
+        where I: IntoIterator<Item = (ArrayBase<S, Ix1>, F)>,
+              S: Data<Elem = F>,
+    {
+        PredictIter::new(self, samples.into_iter())
+    }
+}
+
+impl<T> Rls<T> {
+
+    /// Returns the number of taps, i.e. the length of the weight vector.
+    pub fn len(&self) -> usize {
+        self.weight.len()
+    }
+
+    /// Returns `true` if the filter has no taps.
+    pub fn is_empty(&self) -> bool {
+        self.weight.is_empty()
+    }
+
+    /// Returns a reference to the gain vector.
+    pub fn gain_ref(&self) -> &Array1<T> {
+        &self.gain
+    }
+
+    /// Returns a reference to the gain vector k(i) of the most recent update, interpreted as a
+    /// Kalman gain. It is zero before the first update.
+    ///
+    /// RLS is the Kalman filter for the state-space model with the weights as the state,
+    /// w(i) = w(i-1), and the targets as measurements, d(i) = u(i) · w(i) + v(i), where forgetting
+    /// takes the place of the process noise by inflating the state covariance P(i-1) by λ^{-1}
+    /// before each measurement update. The measurement update w(i) = w(i-1) + k(i) e(i) then uses
+    /// the gain vector as the Kalman gain, and P(i) is the state covariance up to the scale of the
+    /// measurement noise v(i). This is the same vector as [`gain_ref`](#method.gain_ref).
+    pub fn kalman_gain(&self) -> &Array1<T> {
+        &self.gain
+    }
+
+    /// Returns a reference to the inverse correlation matrix.
+    pub fn inverse_correlation_ref(&self) -> &Array2<T> {
+        &self.inverse_correlation
+    }
+
+    /// Returns a reference to the inverse forgetting factor.
+    pub fn inv_forgetting_factor_ref(&self) -> &T {
+        &self.inv_forgetting_factor
+    }
+
+    /// Returns a reference to the (tap) weight vector.
+    pub fn weight_ref(&self) -> &Array1<T> {
+        &self.weight
+    }
+
+    /// Returns a mutable reference to the (tap) weight vector.
+    ///
+    /// The inverse correlation matrix is not re-derived after modifying the weight vector, so
+    /// the caller is responsible for keeping the two consistent.
+    pub fn weight_mut(&mut self) -> &mut Array1<T> {
+        &mut self.weight
+    }
+
+    /// Returns a refernce to the prior error.
+    ///
+    /// The prior error is an output of the filter: every update overwrites it with
+    /// d(i) - w(i-1) · u(i) of that update.
+    pub fn prior_error_ref(&self) -> &T {
+        &self.prior_error
+    }
+
+    /// Sets the prior error, e.g. to seed it for a custom recursion or stopping criterion built
+    /// on top of the filter.
+    ///
+    /// Since every update overwrites the prior error, this only affects what is read before the
+    /// next update.
+    pub fn set_prior_error(&mut self, prior_error: T) {
+        self.prior_error = prior_error;
+    }
+
+    /// Returns a reference to the Tikhonov regularization μ.
+    pub fn regularization_ref(&self) -> &T {
+        &self.regularization
+    }
+
+    /// Returns a reference to the leakage γ.
+    pub fn leakage_ref(&self) -> &T {
+        &self.leakage
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::Lcg;
+
+    #[test]
+    fn update_returns_prior_error() {
+        let mut rng = Lcg::new(1);
+        let mut rls = Rls::new(0.1, 0.99, 3);
+        for _ in 0..20 {
+            let input = rng.array(3);
+            let target = rng.next();
+            let expected = target - rls.weight_ref().dot(&input);
+            assert_eq!(rls.update(&input, target), expected);
+            assert_eq!(*rls.prior_error_ref(), expected);
+        }
+    }
+
+    #[test]
+    fn reset_equals_new_filter() {
+        let mut rng = Lcg::new(2);
+        let mut rls = Rls::new(0.1, 0.98, 4);
+        for _ in 0..50 {
+            let input = rng.array(4);
+            rls.update(&input, rng.next());
+        }
+        rls.reset(0.5);
+        assert_eq!(rls, Rls::new(0.5, 0.98, 4));
+    }
+
+    #[test]
+    fn weight_mut_changes_predictions() {
+        let mut rls = Rls::new(0.1, 1.0, 2);
+        let input = arr1(&[1.0, 2.0]);
+        assert_eq!(rls.predict(&input), 0.0);
+        rls.weight_mut()[1] = 3.0;
+        assert_eq!(rls.predict(&input), 6.0);
+    }
+
+    #[test]
+    fn update_batch_matches_loop() {
+        let mut rng = Lcg::new(3);
+        let inputs = Array2::from_shape_fn((30, 3), |_| rng.next());
+        let targets = rng.array(30);
+
+        let mut batch = Rls::new(0.1, 0.99, 3);
+        let prior_errors = batch.update_batch(&inputs, &targets);
+
+        let mut sequential = Rls::new(0.1, 0.99, 3);
+        for ((input, &target), &prior_error) in inputs.outer_iter().zip(targets.iter()).zip(prior_errors.iter()) {
+            assert_eq!(sequential.update(&input, target), prior_error);
+        }
+        assert_eq!(batch, sequential);
+    }
+
+    #[test]
+    fn rank1_update_matches_outer_product() {
+        let mut rng = Lcg::new(12);
+        let mut a = Array2::from_shape_fn((4, 4), |_| rng.next());
+        let x = rng.array(4);
+        let y = rng.array(4);
+        let expected = Array2::from_shape_fn((4, 4), |(i, j)| a[[i, j]] - x[i] * y[j]);
+        rank1_update(&mut a, &x, &y);
+        for (a, b) in a.iter().zip(expected.iter()) {
+            assert!((a - b).abs() < 1e-15);
+        }
+    }
+
+    #[test]
+    fn leakage_decays_weights_geometrically_without_input() {
+        let mut rls = Rls::with_leakage(0.1, 0.9, 2, 0.1);
+        rls.weight_mut().assign(&arr1(&[1.0, -2.0]));
+        let input = Array1::zeros(2);
+        let factor: f64 = 1.0 - 0.9 * 0.1;
+        for k in 1..20 {
+            rls.update(&input, 0.0);
+            let expected = factor.powi(k);
+            assert!((rls.weight_ref()[0] - expected).abs() < 1e-12);
+            assert!((rls.weight_ref()[1] + 2.0 * expected).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "bincode")]
+    fn serde_round_trip_updates_identically() {
+        let mut rng = Lcg::new(14);
+        let mut rls = Rls::new(0.1, 0.99, 3);
+        for _ in 0..20 {
+            let input = rng.array(3);
+            rls.update(&input, rng.next());
+        }
+
+        let bytes = ::bincode::serialize(&rls).unwrap();
+        let mut restored: Rls<f64> = ::bincode::deserialize(&bytes).unwrap();
+        assert_eq!(restored, rls);
+        for _ in 0..20 {
+            let input = rng.array(3);
+            let target = rng.next();
+            assert_eq!(restored.update(&input, target), rls.update(&input, target));
+        }
+        assert_eq!(restored, rls);
+    }
+
+    #[test]
+    fn single_and_double_precision_converge_alike() {
+        let mut rng = Lcg::new(16);
+        let weight = arr1(&[0.5, -1.5, 2.0]);
+        let mut single = Rls::<f32>::new(1e-3, 1.0, 3);
+        let mut double = Rls::<f64>::new(1e-3, 1.0, 3);
+        for _ in 0..200 {
+            let input = rng.array(3);
+            let target = weight.dot(&input);
+            single.update(&input.mapv(|x| x as f32), target as f32);
+            double.update(&input, target);
+        }
+        for ((&s, &d), &w) in single.weight_ref().iter().zip(double.weight_ref().iter()).zip(weight.iter()) {
+            assert!((d - w).abs() < 1e-3);
+            assert!((f64::from(s) - d).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    fn standard_errors_shrink_with_more_samples() {
+        let mut rng = Lcg::new(17);
+        let weight = arr1(&[1.0, -1.0]);
+        let mut rls = Rls::new(1.0, 1.0, 2);
+        let mut previous = rls.weight_std_err(0.01);
+        for _ in 0..5 {
+            for _ in 0..100 {
+                let input = rng.array(2);
+                let target = weight.dot(&input) + 0.1 * rng.next();
+                rls.update(&input, target);
+            }
+            let std_err = rls.weight_std_err(0.01);
+            assert!(std_err.iter().zip(previous.iter()).all(|(s, p)| s < p));
+            previous = std_err;
+        }
+    }
+
+    #[test]
+    fn residual_variance_converges_to_noise_variance() {
+        let mut rng = Lcg::new(18);
+        let weight = arr1(&[2.0, 0.5, -1.0]);
+        // Uniform noise of width 0.2 has variance 0.2² / 12.
+        let noise_variance = 0.04 / 12.0;
+        let mut rls = Rls::new(0.1, 0.999, 3);
+        for _ in 0..10000 {
+            let input = rng.array(3);
+            let target = weight.dot(&input) + 0.2 * rng.next();
+            rls.update(&input, target);
+        }
+        assert!((rls.residual_variance() / noise_variance - 1.0).abs() < 0.1);
+    }
+
+    /// Returns the largest element of |P - Pᵀ|.
+    fn max_asymmetry(rls: &Rls<f64>) -> f64 {
+        let p = rls.inverse_correlation_ref();
+        (p - &p.t()).iter().fold(0.0, |max, x| x.abs().max(max))
+    }
+
+    #[test]
+    fn symmetrization_keeps_inverse_correlation_symmetric() {
+        let mut rng = Lcg::new(21);
+        let mut plain = Rls::new(0.01, 0.98, 6);
+        let mut symmetrized = plain.clone();
+        symmetrized.set_symmetrize_every(1);
+        for _ in 0..5000 {
+            let input = rng.array(6);
+            let target = rng.next();
+            plain.update(&input, target);
+            symmetrized.update(&input, target);
+        }
+        assert_eq!(max_asymmetry(&symmetrized), 0.0);
+        assert!(max_asymmetry(&plain) > 0.0);
+    }
+
+    #[test]
+    fn predict_batch_matches_predict() {
+        let mut rng = Lcg::new(23);
+        let rls = Rls::with_weight(0.1, 1.0, rng.array(3));
+        let inputs = Array2::from_shape_fn((10, 3), |_| rng.next());
+        let mut out = Array1::zeros(10);
+        rls.predict_batch(&inputs, &mut out);
+        for (input, &y) in inputs.outer_iter().zip(out.iter()) {
+            assert!((rls.predict(&input) - y).abs() < 1e-15);
+        }
+    }
+
+    #[test]
+    fn covariance_reset_readapts_faster_after_step_change() {
+        let mut rng = Lcg::new(28);
+        let mut stalled = Rls::new(0.01, 1.0, 2);
+        for _ in 0..5000 {
+            let input = rng.array(2);
+            stalled.update(&input, input[0] + input[1]);
+        }
+        let mut reset = stalled.clone();
+        reset.reset_covariance(0.01);
+        let mut triggered = stalled.clone();
+        triggered.reset_covariance_when_trace_below(1e-2, 0.01);
+
+        for _ in 0..50 {
+            let input = rng.array(2);
+            let target = 3.0 * input[0] - input[1];
+            stalled.update(&input, target);
+            reset.update(&input, target);
+            triggered.update(&input, target);
+        }
+        let error = |rls: &Rls<f64>| (rls.weight_ref() - &arr1(&[3.0, -1.0])).iter().fold(0.0f64, |max, x| x.abs().max(max));
+        assert!(error(&stalled) > 0.1);
+        assert!(error(&reset) < 1e-2);
+        assert!(error(&triggered) < 1e-2);
+    }
+
+    #[test]
+    fn weights_round_trip_through_slices() {
+        let rls = Rls::from_slice(0.1, 0.99, &[1.0, -2.0, 3.5]);
+        let weights = rls.weights_to_vec();
+        assert_eq!(weights, vec![1.0, -2.0, 3.5]);
+        assert_eq!(Rls::from_slice(0.1, 0.99, &weights), rls);
+    }
+
+    #[test]
+    fn slice_updates_match_array_updates() {
+        let mut rng = Lcg::new(30);
+        let mut array = Rls::new(0.1, 0.99, 3);
+        let mut slice = array.clone();
+        for _ in 0..20 {
+            let input = rng.array(3);
+            let target = rng.next();
+            assert_eq!(slice.predict_slice(input.as_slice().unwrap()), array.predict(&input));
+            assert_eq!(slice.update_slice(input.as_slice().unwrap(), target), array.update(&input, target));
+        }
+        assert_eq!(slice, array);
+    }
+
+    #[test]
+    fn frozen_update_leaves_state_unchanged() {
+        let mut rng = Lcg::new(31);
+        let mut rls = Rls::new(0.1, 0.99, 3);
+        for _ in 0..10 {
+            let input = rng.array(3);
+            rls.update(&input, rng.next());
+        }
+        let weight = rls.weight_ref().clone();
+        let inverse_correlation = rls.inverse_correlation_ref().clone();
+
+        rls.freeze();
+        let input = rng.array(3);
+        let expected = 1.0 - weight.dot(&input);
+        assert_eq!(rls.update(&input, 1.0), expected);
+        assert_eq!(rls.weight_ref(), &weight);
+        assert_eq!(rls.inverse_correlation_ref(), &inverse_correlation);
+
+        rls.unfreeze();
+        rls.update(&input, 1.0);
+        assert!(rls.weight_ref() != &weight);
+    }
+
+    #[test]
+    fn robust_update_rejects_outlier() {
+        let mut rng = Lcg::new(32);
+        let mut plain = Rls::new(0.1, 1.0, 2);
+        for _ in 0..200 {
+            let input = rng.array(2);
+            plain.update(&input, input[0] - input[1] + 0.01 * rng.next());
+        }
+        let mut robust = plain.clone();
+        let weight = plain.weight_ref().clone();
+
+        let input = arr1(&[0.5, 0.5]);
+        plain.update(&input, 100.0);
+        robust.update_robust(&input, 100.0, 0.1);
+        let shift = |rls: &Rls<f64>| (rls.weight_ref() - &weight).iter().fold(0.0f64, |max, x| x.abs().max(max));
+        assert!(shift(&plain) > 0.1);
+        assert!(shift(&robust) < 1e-2 * shift(&plain));
+    }
+
+    #[test]
+    fn robust_update_rejects_non_positive_threshold() {
+        let mut rls = Rls::new(0.1, 1.0, 2);
+        let input = arr1(&[1.0, 0.0]);
+        assert_eq!(rls.try_update_robust(&input, 1.0, 0.0), Err(RlsError::InvalidHuberDelta));
+        assert_eq!(rls.try_update_robust(&input, 1.0, -1.0), Err(RlsError::InvalidHuberDelta));
+        assert_eq!(rls, Rls::new(0.1, 1.0, 2));
+    }
+
+    #[test]
+    #[should_panic(expected = "Huber threshold must be positive")]
+    fn robust_update_panics_on_zero_threshold() {
+        Rls::new(0.1, 1.0, 2).update_robust(&arr1(&[1.0, 0.0]), 1.0, 0.0);
+    }
+
+    #[test]
+    fn checked_update_rejects_nan_input() {
+        let mut rng = Lcg::new(34);
+        let mut rls = Rls::builder()
+            .initialization_factor(0.1)
+            .forgetting_factor(0.99)
+            .taps(3)
+            .error_window(4)
+            .record_history(true)
+            .build()
+            .unwrap();
+        for _ in 0..10 {
+            let input = rng.array(3);
+            rls.checked_update(&input, rng.next()).unwrap();
+        }
+        let previous = rls.clone();
+
+        let input = arr1(&[1.0, f64::NAN, 0.0]);
+        assert_eq!(rls.checked_update(&input, 1.0), Err(RlsError::NonFinite));
+        assert_eq!(rls, previous);
+        assert_eq!(rls.weight_history().len(), 10);
+    }
+
+    #[test]
+    fn clone_reset_equals_new_filter() {
+        let mut rng = Lcg::new(35);
+        let mut rls = Rls::new(0.5, 0.97, 3);
+        for _ in 0..20 {
+            let input = rng.array(3);
+            rls.update(&input, rng.next());
+        }
+        assert_eq!(rls.clone_reset(), Rls::new(0.5, 0.97, 3));
+    }
+
+    #[test]
+    fn weight_delta_norm_decays_on_stationary_system() {
+        let mut rng = Lcg::new(36);
+        let weight = arr1(&[1.0, 2.0, -1.0]);
+        let mut rls = Rls::new(0.1, 1.0, 3);
+        assert_eq!(rls.last_weight_delta_norm(), 0.0);
+        for _ in 0..20 {
+            let input = rng.array(3);
+            rls.update(&input, weight.dot(&input) + 0.001 * rng.next());
+        }
+        assert!(!rls.has_converged(1e-5));
+        for _ in 0..2000 {
+            let input = rng.array(3);
+            rls.update(&input, weight.dot(&input) + 0.001 * rng.next());
+        }
+        assert!(rls.has_converged(1e-5));
+    }
+
+    #[test]
+    fn regularization_shrinks_weights_of_ill_posed_fit() {
+        let mut rng = Lcg::new(37);
+        let mut plain = Rls::new(1e-3, 0.99, 2);
+        let mut regularized = Rls::with_regularization(1e-3, 0.99, 2, 1.0);
+        for _ in 0..500 {
+            let x = rng.next();
+            let input = arr1(&[x, x + 1e-3 * rng.next()]);
+            let target = x + 0.1 * rng.next();
+            plain.update(&input, target);
+            regularized.update(&input, target);
+        }
+        assert!(regularized.weights_l2_norm() < plain.weights_l2_norm());
+    }
+
+    #[test]
+    fn num_updates_counts_all_updates() {
+        let mut rng = Lcg::new(39);
+        let mut rls = Rls::new(0.1, 0.99, 2);
+        for _ in 0..5 {
+            let input = rng.array(2);
+            rls.update(&input, rng.next());
+        }
+        let inputs = Array2::from_shape_fn((7, 2), |_| rng.next());
+        rls.update_batch(&inputs, &rng.array(7));
+        rls.update_block(&inputs, &rng.array(7));
+        assert_eq!(rls.num_updates(), 19);
+
+        rls.freeze();
+        rls.update(&inputs.row(0), 1.0);
+        assert_eq!(rls.num_updates(), 19);
+        rls.reset(0.1);
+        assert_eq!(rls.num_updates(), 0);
+    }
+
+    #[test]
+    fn bias_recovers_slope_and_intercept() {
+        let mut rng = Lcg::new(40);
+        let mut rls = Rls::with_bias(1e-3, 1.0, 1);
+        assert!(rls.has_bias());
+        for _ in 0..200 {
+            let x = rng.next();
+            rls.update(&arr1(&[x]), 3.0 * x - 2.0);
+        }
+        let weight = rls.weight_ref();
+        assert!((weight[0] + 2.0).abs() < 1e-3);
+        assert!((weight[1] - 3.0).abs() < 1e-3);
+        assert!((rls.predict(&arr1(&[1.0])) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn merging_halves_beats_either_half() {
+        let mut rng = Lcg::new(42);
+        let data: Vec<_> = (0..60).map(|_| {
+            let input = rng.array(2);
+            let target = input[0] - 0.5 * input[1] + 0.3 * rng.next();
+            (input, target)
+        }).collect();
+
+        let mut full = Rls::<f64>::new(0.01, 1.0, 2);
+        let mut first = full.clone();
+        let mut second = full.clone();
+        for (i, &(ref input, target)) in data.iter().enumerate() {
+            full.update(input, target);
+            if i < 30 {
+                first.update(input, target);
+            } else {
+                second.update(input, target);
+            }
+        }
+
+        let distance = |rls: &Rls<f64>| {
+            let difference = rls.weight_ref() - full.weight_ref();
+            difference.dot(&difference).sqrt()
+        };
+        let (first_distance, second_distance) = (distance(&first), distance(&second));
+        first.merge(&second, 1.0, 1.0).unwrap();
+        assert!(distance(&first) < first_distance.min(second_distance));
+    }
+
+    #[test]
+    fn merge_rejects_mismatched_filters() {
+        let mut rls = Rls::<f64>::new(0.1, 0.99, 2);
+        let original = rls.clone();
+        assert_eq!(rls.merge(&Rls::new(0.1, 0.99, 3), 1.0, 1.0),
+            Err(RlsError::DimensionMismatch { expected: 2, got: 3 }));
+        assert_eq!(rls.merge(&Rls::new(0.1, 0.9, 2), 1.0, 1.0),
+            Err(RlsError::ForgettingFactorMismatch));
+        assert_eq!(rls.merge(&Rls::with_bias(0.1, 0.99, 1), 1.0, 1.0),
+            Err(RlsError::BiasMismatch));
+        assert_eq!(rls, original);
+    }
+
+    #[test]
+    fn merge_restarts_weight_smoothing() {
+        let mut rls = Rls::<f64>::builder()
+            .initialization_factor(0.1)
+            .forgetting_factor(0.99)
+            .taps(2)
+            .weight_smoothing(0.1)
+            .build()
+            .unwrap();
+        let mut other = Rls::new(0.1, 0.99, 2);
+        other.update(&arr1(&[1.0, 0.5]), 2.0);
+
+        rls.merge(&other, 1.0, 3.0).unwrap();
+        assert_eq!(rls.smoothed_weight(), rls.weight_ref());
+    }
+
+    #[test]
+    fn condition_estimate_detects_collinear_inputs() {
+        let mut rng = Lcg::new(44);
+        let mut well_conditioned = Rls::<f64>::new(0.1, 1.0, 3);
+        let mut collinear = Rls::<f64>::new(0.1, 1.0, 3);
+        let mut estimates = Vec::new();
+        for i in 0..1000 {
+            let input = rng.array(3);
+            well_conditioned.update(&input, input.scalar_sum());
+
+            let x = rng.next();
+            let input = arr1(&[x, x + 1e-3 * rng.next(), -x]);
+            collinear.update(&input, x);
+            if i % 250 == 249 {
+                estimates.push(collinear.covariance_condition_estimate());
+            }
+        }
+
+        assert!(well_conditioned.covariance_condition_estimate() < 1.5);
+        assert!(estimates.windows(2).all(|pair| pair[1] > pair[0]));
+        assert!(estimates[0] > 100.0);
+    }
+
+    #[test]
+    fn directional_forgetting_prevents_windup() {
+        let input = arr1(&[1.0, 0.5]);
+        let mut plain = Rls::<f64>::new(1.0, 0.95, 2);
+        let mut directional = plain.clone();
+        for _ in 0..500 {
+            plain.update(&input, 2.0);
+            directional.update_directional(&input, 2.0);
+        }
+
+        assert!(plain.covariance_trace() > 1e6);
+        assert!(directional.covariance_trace() < 10.0);
+        assert!((directional.predict(&input) - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn grown_filter_keeps_updating_without_nan() {
+        let mut rng = Lcg::new(47);
+        let mut rls = Rls::<f64>::new(0.1, 0.99, 2);
+        for _ in 0..200 {
+            let input = rng.array(3);
+            rls.update(&input.view().split_at(Axis(0), 2).0, input[0] - input[1] + 0.5 * input[2]);
+        }
+
+        rls.grow(1, 0.1);
+        assert_eq!(rls.input_len(), 3);
+        assert_eq!(rls.weight_ref()[2], 0.0);
+        for _ in 0..200 {
+            let input = rng.array(3);
+            let error = rls.update(&input, input[0] - input[1] + 0.5 * input[2]);
+            assert!(error.is_finite());
+        }
+
+        assert!(rls.weight_ref().iter().all(|w| w.is_finite()));
+        assert!(rls.inverse_correlation_ref().iter().all(|p| p.is_finite()));
+        assert!((rls.weight_ref() - &arr1(&[1.0, -1.0, 0.5])).iter().all(|d| d.abs() < 1e-2));
+    }
+
+    #[test]
+    fn pruning_a_zero_tap_barely_changes_predictions() {
+        let mut rng = Lcg::new(48);
+        let mut rls = Rls::<f64>::new(0.1, 1.0, 3);
+        for _ in 0..300 {
+            let input = rng.array(3);
+            rls.update(&input, 2.0 * input[0] - input[2] + 1e-3 * rng.next());
+        }
+        let before = rls.clone();
+
+        rls.prune(&[1]);
+        assert_eq!(rls.input_len(), 2);
+        for _ in 0..20 {
+            let input = rng.array(3);
+            let pruned_input = arr1(&[input[0], input[2]]);
+            assert!((rls.predict(&pruned_input) - before.predict(&input)).abs() < 1e-3);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "given more than once")]
+    fn prune_rejects_duplicate_indices() {
+        Rls::<f64>::new(0.1, 1.0, 3).prune(&[0, 0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn prune_rejects_out_of_range_indices() {
+        Rls::<f64>::new(0.1, 1.0, 3).prune(&[3]);
+    }
+
+    #[test]
+    fn set_weight_replaces_weight_and_covariance() {
+        let mut rls = Rls::<f64>::builder()
+            .initialization_factor(0.1)
+            .forgetting_factor(0.99)
+            .taps(2)
+            .weight_smoothing(0.1)
+            .build()
+            .unwrap();
+        for _ in 0..10 {
+            rls.update(&arr1(&[1.0, -0.5]), 1.0);
+        }
+        let inverse_correlation = rls.inverse_correlation_ref().clone();
+
+        assert_eq!(rls.set_weight(arr1(&[1.0, 2.0, 3.0]), None),
+            Err(RlsError::DimensionMismatch { expected: 2, got: 3 }));
+
+        rls.set_weight(arr1(&[3.0, 4.0]), None).unwrap();
+        assert_eq!(rls.weight_ref(), &arr1(&[3.0, 4.0]));
+        assert_eq!(rls.smoothed_weight(), &arr1(&[3.0, 4.0]));
+        assert_eq!(rls.inverse_correlation_ref(), &inverse_correlation);
+
+        rls.set_weight(arr1(&[1.0, 2.0]), Some(10.0)).unwrap();
+        assert_eq!(rls.weight_ref(), &arr1(&[1.0, 2.0]));
+        assert_eq!(rls.inverse_correlation_ref(), &(Array2::eye(2) * 0.1));
+    }
+
+    #[test]
+    fn single_precision_copy_predicts_within_round_off() {
+        let mut rng = Lcg::new(51);
+        let mut rls = Rls::<f64>::new(0.1, 0.99, 3);
+        for _ in 0..200 {
+            let input = rng.array(3);
+            rls.update(&input, 3.0 * input[0] - input[1] + 0.5 * input[2]);
+        }
+
+        let single = rls.to_f32();
+        for _ in 0..20 {
+            let input = rng.array(3);
+            let prediction = single.predict(&input.mapv(|x| x as f32));
+            assert!((f64::from(prediction) - rls.predict(&input)).abs() < 1e-5);
+        }
+        assert!(single.to_f64().approx_eq(&rls, 1e-6));
+    }
+
+    #[test]
+    fn sign_error_update_converges_more_slowly() {
+        let mut rng = Lcg::new(52);
+        let true_weight = arr1(&[1.0, -2.0]);
+        let mut plain = Rls::<f64>::new(0.1, 1.0, 2);
+        let mut sign_error = plain.clone();
+        let distance = |rls: &Rls<f64>| {
+            let difference = rls.weight_ref() - &true_weight;
+            difference.dot(&difference).sqrt()
+        };
+
+        for i in 0..2000 {
+            let input = rng.array(2);
+            let target = true_weight.dot(&input) + 0.01 * rng.next();
+            plain.update(&input, target);
+            sign_error.update_sign_error(&input, target);
+            if i == 50 {
+                assert!(distance(&sign_error) > distance(&plain));
+            }
+        }
+
+        assert!(distance(&sign_error) < 0.05);
+    }
+
+    #[test]
+    fn step_report_matches_independent_computation() {
+        let mut rng = Lcg::new(53);
+        let mut rls = Rls::<f64>::new(0.1, 0.99, 3);
+        for _ in 0..20 {
+            let input = rng.array(3);
+            let target = input.scalar_sum();
+            let weight = rls.weight_ref().clone();
+            let report = rls.update_reporting(&input, target);
+
+            let delta = rls.weight_ref() - &weight;
+            assert!((report.prior_error - (target - weight.dot(&input))).abs() < 1e-12);
+            assert!((report.gain_norm - rls.gain_ref().dot(rls.gain_ref()).sqrt()).abs() < 1e-12);
+            assert!((report.weight_delta_norm - delta.dot(&delta).sqrt()).abs() < 1e-12);
+            assert!((report.covariance_trace - rls.inverse_correlation_ref().diag().scalar_sum()).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn approx_eq_tolerates_round_off_only() {
+        let mut rls = Rls::<f64>::new(0.1, 0.99, 2);
+        rls.update(&arr1(&[1.0, 0.5]), 1.0);
+        let mut other = rls.clone();
+        assert_eq!(other, rls);
+
+        other.weight_mut()[0] += 1e-10;
+        assert!(other != rls);
+        assert!(other.approx_eq(&rls, 1e-8));
+        assert!(!other.approx_eq(&rls, 1e-12));
+
+        assert!(!rls.approx_eq(&Rls::new(0.1, 0.99, 3), 1.0));
+        assert!(!rls.approx_eq(&Rls::new(0.1, 0.5, 2), 1e-3));
+    }
+
+    #[test]
+    fn windowed_rmse_covers_last_errors() {
+        let mut rls = Rls::<f64>::builder()
+            .initialization_factor(0.1)
+            .forgetting_factor(0.99)
+            .taps(1)
+            .error_window(3)
+            .build()
+            .unwrap();
+        // The weights of a frozen filter stay zero, so the prior errors are the targets.
+        rls.freeze();
+        assert_eq!(rls.windowed_rmse(), 0.0);
+
+        rls.update(&arr1(&[1.0]), 3.0);
+        assert_eq!(rls.windowed_rmse(), 3.0);
+        rls.update(&arr1(&[1.0]), -4.0);
+        assert!((rls.windowed_rmse() - (25.0f64 / 2.0).sqrt()).abs() < 1e-12);
+        for &target in &[1.0, 2.0, -2.0] {
+            rls.update(&arr1(&[1.0]), target);
+        }
+        assert!((rls.windowed_rmse() - 3.0f64.sqrt()).abs() < 1e-12);
+
+        assert_eq!(Rls::<f64>::new(0.1, 0.99, 1).windowed_rmse(), 0.0);
+    }
+
+    fn compare_block_with_sequential(mut block: Rls<f64>) {
+        let mut rng = Lcg::new(56);
+        let mut sequential = block.clone();
+        let n = block.input_len();
+        for _ in 0..10 {
+            let inputs = Array2::from_shape_fn((4, n), |_| rng.next());
+            let targets = inputs.sum_axis(Axis(1)) + Array1::from_shape_fn(4, |_| 0.1 * rng.next());
+            block.update_block(&inputs, &targets);
+            for (input, &target) in inputs.outer_iter().zip(targets.iter()) {
+                sequential.update(&input, target);
+            }
+        }
+
+        assert_eq!(block.num_updates(), sequential.num_updates());
+        let weight_difference = block.weight_ref() - sequential.weight_ref();
+        let inverse_correlation_difference = block.inverse_correlation_ref()
+            - sequential.inverse_correlation_ref();
+        assert!(weight_difference.iter()
+            .chain(inverse_correlation_difference.iter())
+            .all(|d| d.abs() < 1e-9));
+
+        assert!((block.prior_error - sequential.prior_error).abs() < 1e-9);
+        assert!((block.residual_variance() - sequential.residual_variance()).abs() < 1e-9);
+        assert!((block.r_squared() - sequential.r_squared()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn block_update_matches_sequential_updates() {
+        compare_block_with_sequential(Rls::new(0.1, 0.95, 3));
+    }
+
+    #[test]
+    fn block_update_with_bias_matches_sequential_updates() {
+        compare_block_with_sequential(Rls::with_bias(0.1, 0.95, 3));
+    }
+
+    #[test]
+    fn down_weighted_outlier_has_less_impact() {
+        let mut rng = Lcg::new(58);
+        let mut rls = Rls::<f64>::new(0.1, 1.0, 2);
+        for _ in 0..100 {
+            let input = rng.array(2);
+            rls.update(&input, input[0] - input[1]);
+        }
+
+        let input = arr1(&[0.4, 0.3]);
+        let mut unit = rls.clone();
+        let mut full = rls.clone();
+        let mut down_weighted = rls.clone();
+        assert_eq!(unit.update_weighted(&input, 10.0, 1.0), rls.update(&input, 10.0));
+        assert_eq!(unit, rls);
+
+        full.update_weighted(&input, 10.0, 1.0);
+        down_weighted.update_weighted(&input, 10.0, 0.01);
+        let shift = |other: &Rls<f64>| {
+            let difference = other.weight_ref() - &arr1(&[1.0, -1.0]);
+            difference.dot(&difference).sqrt()
+        };
+        assert!(shift(&down_weighted) < 0.05 * shift(&full));
+    }
+
+    #[test]
+    fn apply_matches_manual_convolution() {
+        let mut rls = Rls::<f64>::new(0.1, 1.0, 3);
+        rls.set_weight(arr1(&[1.0, -2.0, 0.5]), None).unwrap();
+        let signal = arr1(&[1.0, 2.0, 3.0, -1.0, 0.0]);
+        // y(i) = x(i) - 2 x(i-1) + 0.5 x(i-2), with zeros before the start.
+        let expected = arr1(&[1.0, 0.0, -0.5, -6.0, 3.5]);
+        assert_eq!(rls.apply(&signal), expected);
+
+        let mut delay_line = DelayLine::new(3);
+        for (&x, &y) in signal.iter().zip(expected.iter()) {
+            delay_line.push(x);
+            assert_eq!(rls.predict(&delay_line.as_array()), y);
+        }
+
+        let mut biased = Rls::<f64>::with_bias(0.1, 1.0, 3);
+        biased.set_weight(arr1(&[2.0, 1.0, -2.0, 0.5]), None).unwrap();
+        assert_eq!(biased.apply(&signal), expected + 2.0);
+    }
+
+    #[test]
+    fn try_constructors_reject_invalid_parameters() {
+        assert!(Rls::<f64>::try_new(0.1, 0.99, 2).is_ok());
+        assert!(Rls::<f64>::try_new(0.1, 1.0, 2).is_ok());
+
+        for &forgetting_factor in &[0.0, -0.5, 1.5, f64::NAN] {
+            assert_eq!(Rls::try_new(0.1, forgetting_factor, 2).unwrap_err(),
+                RlsError::InvalidForgettingFactor);
+        }
+        for &initialization_factor in &[0.0, -1.0, f64::NAN] {
+            assert_eq!(Rls::try_new(initialization_factor, 0.99, 2).unwrap_err(),
+                RlsError::InvalidInitializationFactor);
+        }
+        assert_eq!(Rls::<f64>::try_new(0.1, 0.99, 0).unwrap_err(), RlsError::NoTaps);
+
+        assert_eq!(Rls::try_with_weight(0.1, 0.0, arr1(&[1.0])).unwrap_err(),
+            RlsError::InvalidForgettingFactor);
+        assert_eq!(Rls::try_with_weight(0.0, 0.99, arr1(&[1.0])).unwrap_err(),
+            RlsError::InvalidInitializationFactor);
+        assert_eq!(Rls::<f64>::try_with_weight(0.1, 0.99, Array1::zeros(0)).unwrap_err(),
+            RlsError::NoTaps);
+        assert_eq!(Rls::try_with_weight(0.1, 0.99, arr1(&[1.0, 2.0])).unwrap(),
+            Rls::with_weight(0.1, 0.99, arr1(&[1.0, 2.0])));
+    }
+
+    #[test]
+    fn set_prior_error_is_overwritten_by_update() {
+        let mut rls = Rls::<f64>::new(0.1, 0.99, 2);
+        rls.set_prior_error(3.5);
+        assert_eq!(*rls.prior_error_ref(), 3.5);
+
+        let error = rls.update(&arr1(&[1.0, 2.0]), 1.0);
+        assert_eq!(*rls.prior_error_ref(), error);
+        assert_eq!(error, 1.0);
+    }
+
+    #[test]
+    fn r_squared_is_close_to_one_on_linear_system() {
+        let mut rng = Lcg::new(65);
+        let mut rls = Rls::<f64>::new(0.1, 0.99, 2);
+        let mut noisy = rls.clone();
+        assert_eq!(rls.r_squared(), 0.0);
+        for _ in 0..500 {
+            let input = rng.array(2);
+            let target = 3.0 * input[0] - 2.0 * input[1];
+            rls.update(&input, target + 1e-3 * rng.next());
+            noisy.update(&input, target + 2.0 * rng.next());
+        }
+
+        assert!(rls.r_squared() > 0.999);
+        assert!(noisy.r_squared() < 0.9);
+
+        let mut constant = Rls::<f64>::new(0.1, 0.99, 1);
+        for _ in 0..10 {
+            constant.update(&arr1(&[1.0]), 2.0);
+        }
+        assert_eq!(constant.r_squared(), 0.0);
+    }
+
+    #[test]
+    fn time_update_grows_covariance_across_gaps() {
+        let mut rng = Lcg::new(66);
+        let mut rls = Rls::<f64>::new(0.1, 0.9, 2);
+        for _ in 0..50 {
+            let input = rng.array(2);
+            rls.update(&input, input[0]);
+        }
+
+        let weight = rls.weight_ref().clone();
+        let inverse_correlation = rls.inverse_correlation_ref().clone();
+        for _ in 0..3 {
+            rls.time_update();
+        }
+        assert_eq!(rls.weight_ref(), &weight);
+        let expected = &inverse_correlation / (0.9 * 0.9 * 0.9);
+        assert!((rls.inverse_correlation_ref() - &expected).iter().all(|d| d.abs() < 1e-12));
+
+        // A gap in the targets evolves the covariance like an update without excitation.
+        let mut unexcited = rls.clone();
+        unexcited.update(&arr1(&[0.0, 0.0]), 1.0);
+        rls.time_update();
+        assert_eq!(rls.weight_ref(), unexcited.weight_ref());
+        assert!((rls.inverse_correlation_ref() - unexcited.inverse_correlation_ref())
+            .iter()
+            .all(|d| d.abs() < 1e-12));
+
+        rls.freeze();
+        let frozen = rls.clone();
+        rls.time_update();
+        assert_eq!(rls, frozen);
+    }
+
+    #[test]
+    fn patch_updates_match_flattened_updates() {
+        let mut rng = Lcg::new(69);
+        let mut patched = Rls::<f64>::new(0.1, 0.99, 6);
+        let mut flattened = patched.clone();
+        for _ in 0..20 {
+            let patch = Array2::from_shape_fn((2, 3), |_| rng.next());
+            let input = Array1::from_iter(patch.iter().cloned());
+            let target = rng.next();
+            assert_eq!(patched.predict_2d(&patch), flattened.predict(&input));
+            assert_eq!(patched.update_2d(&patch, target), flattened.update(&input, target));
+        }
+        assert_eq!(patched, flattened);
+
+        // The flattening is row-major also for transposed views.
+        let patch = Array2::from_shape_fn((3, 2), |_| rng.next());
+        let input = Array1::from_iter(patch.t().iter().cloned());
+        assert_eq!(patched.predict_2d(&patch.t()), flattened.predict(&input));
+    }
+
+    #[test]
+    fn restore_rolls_back_an_update() {
+        let mut rng = Lcg::new(70);
+        let mut rls = Rls::<f64>::new(0.1, 0.99, 3);
+        for _ in 0..10 {
+            let input = rng.array(3);
+            rls.update(&input, input[0]);
+        }
+        let mut direct = rls.clone();
+
+        let (first, second) = (rng.array(3), rng.array(3));
+        let snapshot = rls.snapshot();
+        rls.update(&first, 5.0);
+        rls.restore(snapshot);
+        assert_eq!(rls, direct);
+
+        assert_eq!(rls.update(&second, 1.0), direct.update(&second, 1.0));
+        assert_eq!(rls, direct);
+    }
+
+    #[test]
+    fn forgetting_schedule_approaches_final_forgetting_factor() {
+        let mut rng = Lcg::new(72);
+        let mut rls = Rls::<f64>::new(0.1, 0.9, 2);
+        rls.set_forgetting_schedule(0.9, 0.999, 0.05);
+
+        let mut forgetting_factors = Vec::new();
+        for i in 0..200 {
+            let input = rng.array(2);
+            rls.update(&input, input[0]);
+            let expected = 0.999 - (0.999 - 0.9) * (-0.05 * i as f64).exp();
+            assert!((rls.forgetting_factor() - expected).abs() < 1e-12);
+            forgetting_factors.push(rls.forgetting_factor());
+        }
+
+        assert!(forgetting_factors.windows(2).all(|pair| pair[1] > pair[0]));
+        assert!((forgetting_factors[199] - 0.999).abs() < 1e-5);
+    }
+
+    #[test]
+    fn cholesky_factors_definite_and_semidefinite_matrices() {
+        let definite: Array2<f64> = arr2(&[[4.0, 2.0, 0.4], [2.0, 5.0, 1.0], [0.4, 1.0, 3.0]]);
+        let factor = cholesky(&definite, false).unwrap();
+        assert!((factor.dot(&factor.t()) - &definite).iter().all(|d| d.abs() < 1e-12));
+        assert!(factor.indexed_iter().all(|((i, j), &l)| j <= i || l == 0.0));
+
+        let semidefinite: Array2<f64> = arr2(&[[1.0, 1.0], [1.0, 1.0]]);
+        assert!(cholesky(&semidefinite, false).is_none());
+        let factor = cholesky(&semidefinite, true).unwrap();
+        assert!((factor.dot(&factor.t()) - &semidefinite).iter().all(|d| d.abs() < 1e-12));
+
+        assert!(cholesky(&arr2(&[[f64::NAN]]), false).is_none());
+        assert!(cholesky(&arr2(&[[-1.0]]), false).is_none());
+    }
+
+    #[test]
+    fn out_of_distribution_inputs_have_higher_predictive_variance() {
+        let mut rng = Lcg::new(74);
+        let mut rls = Rls::<f64>::new(0.1, 1.0, 2);
+        for _ in 0..200 {
+            // The inputs lie close to the line u_1 = u_0.
+            let x = rng.next();
+            let input = arr1(&[x, x + 0.01 * rng.next()]);
+            rls.update(&input, x);
+        }
+
+        let in_distribution = rls.predict_variance(&arr1(&[0.3, 0.3]), 0.1);
+        let out_of_distribution = rls.predict_variance(&arr1(&[0.3, -0.3]), 0.1);
+        assert!(in_distribution > 0.0);
+        assert!(out_of_distribution > 100.0 * in_distribution);
+        assert_eq!(rls.predict_variance(&arr1(&[0.0, 0.0]), 0.1), 0.0);
+    }
+
+    #[test]
+    fn initialization_factor_is_stored() {
+        assert_eq!(Rls::<f64>::new(0.25, 0.99, 2).initialization_factor(), 0.25);
+        assert_eq!(Rls::with_weight(4.0, 0.99, arr1(&[1.0, 2.0])).initialization_factor(), 4.0);
+        assert_eq!(Rls::<f64>::with_bias(0.5, 0.99, 2).initialization_factor(), 0.5);
+
+        let mut rls = Rls::<f64>::new(0.25, 0.99, 2);
+        rls.reset(2.0);
+        assert_eq!(rls.initialization_factor(), 2.0);
+    }
+
+    #[test]
+    fn weight_history_records_every_update() {
+        let mut rng = Lcg::new(78);
+        let mut rls = Rls::<f64>::builder()
+            .initialization_factor(0.1)
+            .forgetting_factor(0.99)
+            .taps(2)
+            .record_history(true)
+            .build()
+            .unwrap();
+        let mut weights = Vec::new();
+        for _ in 0..7 {
+            let input = rng.array(2);
+            rls.update(&input, input[0]);
+            weights.push(rls.weight_ref().clone());
+        }
+
+        assert_eq!(rls.weight_history().len(), 7);
+        assert_eq!(rls.weight_history(), &weights[..]);
+        assert!(Rls::<f64>::new(0.1, 0.99, 2).weight_history().is_empty());
+    }
+
+    #[test]
+    fn group_sparse_update_zeroes_irrelevant_group() {
+        let mut rng = Lcg::new(79);
+        let mut rls = Rls::<f64>::new(0.1, 0.99, 4);
+        let groups = [0..2, 2..4];
+        for _ in 0..300 {
+            let input = rng.array(4);
+            let target = input[0] - 2.0 * input[1] + 0.01 * rng.next();
+            rls.update_group_sparse(&input, target, &groups, 1e-3);
+        }
+
+        let weight = rls.weight_ref();
+        assert_eq!(weight[2], 0.0);
+        assert_eq!(weight[3], 0.0);
+        // The shrinkage in every update biases the relevant group towards zero.
+        assert!((weight[0] - 1.0).abs() < 0.1);
+        assert!((weight[1] + 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn equal_dual_forgetting_factors_reproduce_standard_update() {
+        let mut rng = Lcg::new(80);
+        let mut standard = Rls::<f64>::new(0.1, 0.97, 3);
+        let mut dual = Rls::<f64>::with_dual_forgetting(0.1, 0.97, 0.97, 3);
+        for _ in 0..50 {
+            let input = rng.array(3);
+            let target = rng.next();
+            assert_eq!(dual.update(&input, target), standard.update(&input, target));
+        }
+        assert!(dual.approx_eq(&standard, 0.0));
+        assert_eq!(dual.gain_ref(), standard.gain_ref());
+
+        let mut different = Rls::<f64>::with_dual_forgetting(0.1, 0.97, 0.99, 3);
+        different.update(&arr1(&[1.0, 0.5, -0.5]), 1.0);
+        assert!((different.covariance_forgetting_factor() - 0.99).abs() < 1e-12);
+        assert!((different.forgetting_factor() - 0.97).abs() < 1e-12);
+    }
+
+    #[test]
+    fn weights_map_back_through_linear_transform() {
+        let mut rng = Lcg::new(81);
+        let transform = arr2(&[[1.0, 1.0, 0.0], [0.0, 2.0, -1.0]]);
+        let mut rls = Rls::<f64>::new(1e-6, 1.0, 2);
+        for _ in 0..100 {
+            let input = rng.array(3);
+            // The target depends on the original inputs only through the transformed ones.
+            let transformed = transform.dot(&input);
+            rls.update(&transformed, 0.5 * transformed[0] - 0.25 * transformed[1]);
+        }
+
+        let weight = rls.weights_in_original_space(&transform);
+        assert!((&weight - &arr1(&[0.5, 0.0, 0.25])).iter().all(|d| d.abs() < 1e-6));
+        assert_eq!(rls.weights_in_original_space(&transform.view()), weight);
+
+        let input = rng.array(3);
+        assert!((weight.dot(&input) - rls.predict(&transform.dot(&input))).abs() < 1e-12);
+    }
+
+    #[test]
+    fn scale_covariance_scales_trace() {
+        let mut rls = Rls::<f64>::new(0.01, 0.99, 2);
+        rls.update(&arr1(&[1.0, 2.0]), 3.0);
+        let weight = rls.weight_ref().clone();
+        let trace = rls.covariance_trace();
+
+        rls.scale_covariance(2.5);
+        assert!((rls.covariance_trace() - 2.5 * trace).abs() < 1e-12 * trace);
+        assert_eq!(rls.weight_ref(), &weight);
+    }
+
+    #[test]
+    fn repair_restores_positive_definiteness() {
+        let mut rls = Rls::<f64>::new(0.01, 0.99, 3);
+        for i in 0..50 {
+            let x = i as f64;
+            rls.update(&arr1(&[x.sin(), x.cos(), 1.0]), x.sin());
+        }
+        assert!(rls.is_positive_definite());
+        assert!(!rls.repair_covariance(0.01));
+
+        // Remove slightly more than the variance along v to make P slightly indefinite.
+        let v = arr1(&[1.0, -1.0, 0.5]);
+        let vv = v.dot(&v);
+        let pv = v.dot(&rls.inverse_correlation.dot(&v)) / vv;
+        for i in 0..3 {
+            for j in 0..3 {
+                rls.inverse_correlation[[i, j]] -= 1.01 * pv * v[i] * v[j] / vv;
+            }
+        }
+        let weight = rls.weight_ref().clone();
+        assert!(!rls.is_positive_definite());
+
+        assert!(rls.repair_covariance(0.01));
+        assert!(rls.is_positive_definite());
+        assert_eq!(rls.weight_ref(), &weight);
+
+        // Without any positive eigenvalue, P is reset instead.
+        rls.inverse_correlation = -Array2::eye(3);
+        assert!(rls.repair_covariance(0.5));
+        assert_eq!(rls.inverse_correlation, Array2::eye(3) * 2.0);
+    }
+
+    #[test]
+    fn tuner_prefers_short_memory_only_for_drifting_weights() {
+        let mut rng = Lcg::new(87);
+        let samples = 2000;
+        let inputs = Array2::from_shape_fn((samples, 2), |_| rng.next());
+        let noise = rng.array(samples) * 0.3;
+        let drifting = Array1::from_shape_fn(samples, |i| {
+            (i as f64 / 100.0).sin() * inputs[[i, 0]] + inputs[[i, 1]] + noise[i]
+        });
+        let stationary = Array1::from_shape_fn(samples, |i| {
+            2.0 * inputs[[i, 0]] - inputs[[i, 1]] + noise[i]
+        });
+        let candidates = [0.5, 0.8, 0.9, 0.95, 0.99, 0.999, 1.0];
+
+        let (forgetting_factor, mse) = Rls::tune_forgetting_factor(&inputs, &drifting, &candidates, 0.01);
+        assert!(forgetting_factor > 0.8 && forgetting_factor < 0.99, "{}", forgetting_factor);
+        let mut rls = Rls::new(0.01, forgetting_factor, 2);
+        let prior_errors = rls.update_batch(&inputs, &drifting);
+        assert!((mse - prior_errors.dot(&prior_errors) / samples as f64).abs() < 1e-12);
+
+        let (forgetting_factor, _) = Rls::tune_forgetting_factor(&inputs, &stationary, &candidates, 0.01);
+        assert!(forgetting_factor >= 0.999, "{}", forgetting_factor);
+    }
+
+    #[test]
+    fn loo_residual_matches_refit_without_sample() {
+        let mut rng = Lcg::new(90);
+        let inputs: Vec<_> = (0..12).map(|_| rng.array(3)).collect();
+        let targets: Vec<_> = inputs.iter()
+            .map(|u| u[0] - 2.0 * u[1] + 0.3 * rng.next())
+            .collect();
+
+        let mut rls = Rls::new(0.1, 1.0, 3);
+        for (input, &target) in inputs.iter().zip(&targets) {
+            rls.update(input, target);
+        }
+
+        for left_out in 0..inputs.len() {
+            let mut refit = Rls::new(0.1, 1.0, 3);
+            for (i, (input, &target)) in inputs.iter().zip(&targets).enumerate() {
+                if i != left_out {
+                    refit.update(input, target);
+                }
+            }
+            let brute_force = targets[left_out] - refit.predict(&inputs[left_out]);
+            let loo = rls.loo_residual(&inputs[left_out], targets[left_out]);
+            assert!((loo - brute_force).abs() < 1e-9, "{}: {} vs {}", left_out, loo, brute_force);
+        }
+    }
+
+    #[test]
+    fn loo_residual_of_latest_sample_is_prior_error() {
+        let mut rng = Lcg::new(90);
+        let mut rls = Rls::new(0.1, 0.9, 3);
+        let mut last = (rng.array(3), 0.0);
+        let mut prior_error = 0.0;
+        for _ in 0..12 {
+            let input = rng.array(3);
+            let target = input[0] - 2.0 * input[1] + 0.3 * rng.next();
+            prior_error = rls.update(&input, target);
+            last = (input, target);
+        }
+        assert!((rls.loo_residual(&last.0, last.1) - prior_error).abs() < 1e-9);
+    }
+
+    #[test]
+    fn normalized_weights_have_unit_norm() {
+        let mut rls = Rls::<f64>::with_weight(0.01, 0.99, arr1(&[3.0, 4.0]));
+        assert_eq!(rls.weights_l2_norm(), 5.0);
+        rls.normalize_weights();
+        assert!((rls.weights_l2_norm() - 1.0).abs() < 1e-15);
+        assert!((rls.weight_ref() - &arr1(&[0.6, 0.8])).iter().all(|x| x.abs() < 1e-15));
+
+        let mut zero = Rls::<f64>::new(0.01, 0.99, 2);
+        zero.normalize_weights();
+        assert_eq!(zero.weight_ref(), &arr1(&[0.0, 0.0]));
+    }
+
+    #[test]
+    fn warm_start_reduces_transient_error() {
+        let mut rng = Lcg::new(93);
+        let scale = arr1(&[100.0, 0.01, 1.0]);
+        let weight = arr1(&[0.01, 50.0, -1.0]);
+        // A few rows, since P then expresses the confidence of as many samples in the zero
+        // weights, but with the scales of the inputs.
+        let batch = Array2::from_shape_fn((5, 3), |(_, j)| scale[j] * rng.next());
+
+        let mut cold = Rls::new(1.0, 0.99, 3);
+        let mut warm = cold.clone();
+        warm.warm_start(&batch, 1e-6).unwrap();
+        assert_eq!(warm.weight_ref(), cold.weight_ref());
+
+        let (mut cold_error, mut warm_error) = (0.0, 0.0);
+        for _ in 0..100 {
+            let input = rng.array(3) * &scale;
+            let target = weight.dot(&input);
+            cold_error += cold.update(&input, target).powi(2);
+            warm_error += warm.update(&input, target).powi(2);
+        }
+        assert!(warm_error < 0.5 * cold_error, "{} vs {}", warm_error, cold_error);
+    }
+
+    #[test]
+    fn warm_start_fit_matches_updates_without_forgetting() {
+        let mut rng = Lcg::new(93);
+        let inputs = Array2::from_shape_fn((50, 3), |_| rng.next());
+        let targets = inputs.dot(&arr1(&[1.0, -2.0, 0.5])) + rng.array(50) * 0.1;
+
+        let mut warm = Rls::new(0.5, 1.0, 3);
+        warm.warm_start_fit(&inputs, &targets, 0.5).unwrap();
+        let mut updated = Rls::new(0.5, 1.0, 3);
+        updated.update_batch(&inputs, &targets);
+
+        assert!((warm.weight_ref() - updated.weight_ref()).iter().all(|x| x.abs() < 1e-10));
+        assert!((warm.inverse_correlation_ref() - updated.inverse_correlation_ref())
+            .iter().all(|x| x.abs() < 1e-10));
+
+        let mut rls = Rls::<f64>::new(0.5, 1.0, 2);
+        assert_eq!(rls.warm_start(&Array2::zeros((3, 2)), 0.0), Err(RlsError::SingularCorrelation));
+    }
+
+    #[test]
+    fn filter_moves_across_threads_and_is_shared() {
+        use std::sync::{Arc, Mutex};
+        use std::thread;
+
+        let shared = Arc::new(Mutex::new(Rls::<f64>::new(0.01, 0.99, 2)));
+        let handles: Vec<_> = (0..4).map(|i| {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || {
+                let input = arr1(&[1.0, i as f64]);
+                shared.lock().unwrap().update(&input, 1.0);
+            })
+        }).collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let rls = Arc::try_unwrap(shared).unwrap().into_inner().unwrap();
+        let prediction = thread::spawn(move || rls.predict(&arr1(&[1.0, 0.0]))).join().unwrap();
+        assert!((prediction - 1.0).abs() < 1e-2);
+    }
+
+    #[test]
+    fn forecast_of_ar1_process_decays_to_mean() {
+        // x(i+1) - μ = a (x(i) - μ) + noise, with the mean μ = c / (1 - a) learned by the bias.
+        let (a, c) = (0.8, 0.4);
+        let mean = c / (1.0 - a);
+        let mut rng = Lcg::new(95);
+        let mut rls = Rls::with_bias(0.01, 1.0, 1);
+        let mut history = vec![mean];
+        for _ in 0..2000 {
+            let x = *history.last().unwrap();
+            let next = c + a * x + rng.next();
+            rls.update(&arr1(&[x]), next);
+            history.push(next);
+        }
+
+        let start = mean + 2.0;
+        let forecast = rls.forecast(&arr1(&[0.0, start]), 50);
+        assert_eq!(forecast[0], rls.predict(&arr1(&[start])));
+        for i in 1..10 {
+            assert!((forecast[i] - mean).abs() < (forecast[i - 1] - mean).abs());
+        }
+        assert!((forecast[49] - mean).abs() < 5e-2);
+
+        // A view works as well as an owned array.
+        let history = Array1::from_vec(history);
+        assert_eq!(rls.forecast(&history.view(), 3), rls.forecast(&history, 3));
+    }
+
+    #[test]
+    fn blas_probe_reflects_feature() {
+        assert_eq!(blas_available(), cfg!(feature = "blas"));
+    }
+
+    #[test]
+    fn error_variance_gain_relates_prior_and_posterior_errors() {
+        let mut rng = Lcg::new(97);
+        let mut rls = Rls::new(0.01, 0.99, 3);
+        for _ in 0..100 {
+            let input = rng.array(3);
+            let target = input[0] - input[1] + 0.1 * rng.next();
+            let gamma = rls.error_variance_gain(&input);
+            assert!(gamma > 0.0 && gamma <= 1.0, "{}", gamma);
+
+            let prior_error = rls.update(&input, target);
+            let posterior_error = target - rls.predict(&input);
+            assert!((posterior_error - gamma * prior_error).abs() < 1e-9);
+        }
+        // A zero input leaves the filter unchanged.
+        assert_eq!(rls.error_variance_gain(&arr1(&[0.0, 0.0, 0.0])), 1.0);
+    }
+
+    #[test]
+    fn gain_denominator_matches_update() {
+        let mut rng = Lcg::new(99);
+        let mut rls = Rls::new(0.01, 0.98, 3);
+        for _ in 0..50 {
+            let input = rng.array(3);
+            let denominator = rls.gain_denominator(&input);
+            let unnormalized_gain = rls.inverse_correlation.dot(&input);
+            rls.update(&input, input[0] + 0.1 * rng.next());
+            // The update computes the gain k = P u / c.
+            assert!((&rls.gain * denominator - &unnormalized_gain).iter().all(|x| x.abs() < 1e-9));
+        }
+    }
+
+    #[test]
+    fn irrelevant_features_get_near_zero_importance() {
+        let mut rng = Lcg::new(100);
+        // The second tap has zero weight and the constant third tap has zero variance.
+        let mut rls = Rls::with_bias(0.1, 1.0, 3);
+        for _ in 0..500 {
+            let input = arr1(&[rng.next(), rng.next(), 3.0]);
+            let target = 2.0 * input[0] + 1.0 + 0.01 * rng.next();
+            rls.update(&input, target);
+        }
+
+        let importance = rls.feature_importance();
+        assert_eq!(importance.len(), 3);
+        assert!(importance[0] > 0.99, "{}", importance);
+        assert!(importance[1] < 1e-2, "{}", importance);
+        assert!(importance[2] < 1e-12, "{}", importance);
+        assert!((importance.scalar_sum() - 1.0).abs() < 1e-12);
+
+        rls.reset(1.0);
+        assert!(rls.feature_importance().iter().all(|&x| x == 0.0));
+    }
+
+    #[test]
+    fn from_parts_rejects_invalid_forgetting_factor() {
+        let parts = || (arr1(&[0.0, 0.0]), Array2::<f64>::eye(2));
+        for &inv_forgetting_factor in &[0.0, -1.0, 0.5, f64::NAN, f64::INFINITY] {
+            let (weight, inverse_correlation) = parts();
+            assert_eq!(Rls::from_parts(inv_forgetting_factor, weight, inverse_correlation).unwrap_err(),
+                RlsError::InvalidForgettingFactor);
+        }
+        let (weight, inverse_correlation) = parts();
+        assert!(Rls::from_parts(1.0 / 0.99, weight, inverse_correlation).is_ok());
     }
 }
@@ -0,0 +1,290 @@
+use ndarray::prelude::*;
+use ndarray::Data;
+use ndarray::linalg::general_mat_vec_mul;
+
+use {update_gain, update_inverse_correlation};
+
+/// The parameters of a multiple-output recursive least squares algorithm.
+///
+/// All outputs are estimated from the same input vector u(i), so the gain vector and the inverse
+/// correlation matrix P(i) are shared between them and only updated once per step. Each row of
+/// the weight matrix W(i) holds the (tap) weights of one output, so that the filter's output is
+/// y(i) = W(i) u(i).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug)]
+pub struct MimoRls<F> {
+
+    /// The inverse forgetting factor λ^{-1}.
+    inv_forgetting_factor: F,
+
+    /// The gain vector shared by all outputs.
+    gain: Array1<F>,
+
+    /// The *inverse correlation matrix* shared by all outputs, initialized as P = δ^{-1} · 𝟙.
+    inverse_correlation: Array2<F>,
+
+    /// The *(tap) weight matrix* W(i) of shape `(n_outputs, n_taps)`.
+    weight: Array2<F>,
+
+    /// The prior errors of all outputs, calculated as the difference between the desired outputs
+    /// and the filter outputs before an update.
+    prior_error: Array1<F>,
+
+    // Scratch vector used for the update of the inverse correlation matrix. It is not serialized
+    // and reallocated by the first update after deserialization.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    temp_vec: Array1<F>,
+}
+
+impl<F: NdFloat> MimoRls<F> {
+
+    /// Constructs a new MimoRls object with initialization factor δ and a weight matrix of shape
+    /// `(n_outputs, n_taps)`.
+    pub fn new(initialization_factor: F, forgetting_factor: F, n_taps: usize, n_outputs: usize) -> Self {
+        let weight = Array2::zeros([n_outputs, n_taps]);
+
+        MimoRls::with_weight(initialization_factor, forgetting_factor, weight)
+    }
+
+    /// Constructs a new MimoRls object with initialization factor δ and pre-defined weight
+    /// matrix W of shape `(n_outputs, n_taps)`.
+    pub fn with_weight(initialization_factor: F, forgetting_factor: F, weight: Array2<F>) -> Self {
+        let one = F::one();
+
+        let (n_outputs, n_taps) = weight.dim();
+
+        let inv_forgetting_factor = one / forgetting_factor;
+
+        let gain = Array1::zeros(n_taps);
+        let prior_error = Array1::zeros(n_outputs);
+
+        let mut inverse_correlation = Array2::eye(n_taps);
+        inverse_correlation *= one/initialization_factor;
+
+        let temp_vec = Array1::zeros(n_taps);
+
+        MimoRls {
+            inv_forgetting_factor,
+            gain,
+            inverse_correlation,
+            weight,
+            prior_error,
+            temp_vec,
+        }
+    }
+
+    /// Computes the filter outputs y(i) = W(i) u(i) without updating the filter state.
+    ///
+    /// **Panics** if the length of `input` does not match the number of taps.
+    pub fn predict<S>(&self, input: &ArrayBase<S, Ix1>) -> Array1<F>
+        where S: Data<Elem = F>
+    {
+        assert_eq!(input.len(), self.weight.cols(),
+            "input of length {} does not match {} taps",
+            input.len(), self.weight.cols());
+        self.weight.dot(input)
+    }
+
+    /// Performs a recursive update of the shared inverse correlation matrix and the weight
+    /// matrix.
+    ///
+    /// Returns the prior errors of all outputs, i.e. the differences between `target` and the
+    /// filter outputs before the update.
+    ///
+    /// Except for reallocating the scratch vector after deserialization, the update works in
+    /// place and does not allocate.
+    ///
+    /// **Panics** if the length of `input` does not match the number of taps, or if the
+    /// length of `target` does not match the number of outputs.
+    pub fn update<S1, S2>(&mut self, input: &ArrayBase<S1, Ix1>, target: &ArrayBase<S2, Ix1>)
+        -> &Array1<F>
+        where S1: Data<Elem = F>,
+              S2: Data<Elem = F>,
+    {
+        assert_eq!(input.len(), self.weight.cols(),
+            "input of length {} does not match {} taps",
+            input.len(), self.weight.cols());
+        assert_eq!(target.len(), self.weight.rows(),
+            "target of length {} does not match {} outputs",
+            target.len(), self.weight.rows());
+
+        // The scratch vector is empty after deserialization.
+        if self.temp_vec.len() != self.weight.cols() {
+            self.temp_vec = Array1::zeros(self.weight.cols());
+        }
+
+        update_gain(
+            &self.inverse_correlation,
+            self.inv_forgetting_factor,
+            input,
+            &mut self.gain,
+        );
+
+        // Calculate the prior errors using the not yet updated tap weights.
+        self.prior_error.assign(target);
+        general_mat_vec_mul(
+            -F::one(),
+            &self.weight,
+            input,
+            F::one(),
+            &mut self.prior_error
+        );
+
+        // Update the tap weights of every output.
+        for (mut weight, &prior_error) in self.weight.outer_iter_mut().zip(self.prior_error.iter()) {
+            weight.scaled_add(prior_error, &self.gain);
+        }
+
+        update_inverse_correlation(
+            &mut self.inverse_correlation,
+            self.inv_forgetting_factor,
+            input,
+            &self.gain,
+            &mut self.temp_vec,
+        );
+
+        &self.prior_error
+    }
+
+    /// Performs a recursive update with the input and the targets given as views, see
+    /// [`update`](#method.update).
+    pub fn update_view(&mut self, input: ArrayView1<F>, target: ArrayView1<F>) -> &Array1<F> {
+        self.update(&input, &target)
+    }
+}
+
+impl<T> MimoRls<T> {
+
+    /// Returns a reference to the shared gain vector.
+    pub fn gain_ref(&self) -> &Array1<T> {
+        &self.gain
+    }
+
+    /// Returns a reference to the shared inverse correlation matrix.
+    pub fn inverse_correlation_ref(&self) -> &Array2<T> {
+        &self.inverse_correlation
+    }
+
+    /// Returns a reference to the inverse forgetting factor.
+    pub fn inv_forgetting_factor_ref(&self) -> &T {
+        &self.inv_forgetting_factor
+    }
+
+    /// Returns a reference to the (tap) weight matrix.
+    pub fn weight_ref(&self) -> &Array2<T> {
+        &self.weight
+    }
+
+    /// Returns a reference to the prior errors of all outputs.
+    pub fn prior_error_ref(&self) -> &Array1<T> {
+        &self.prior_error
+    }
+}
+
+/// A bank of scalar recursive least squares filters over a common regressor, e.g. for channel
+/// equalization.
+///
+/// This wraps a [`MimoRls`](struct.MimoRls.html) with one output per target: the inverse
+/// correlation matrix and the gain are only computed once per step, and
+/// [`update_bank`](#method.update_bank) returns the prior errors of all targets. The result
+/// matches independent [`Rls`](struct.Rls.html) filters with the same parameters, one per
+/// target.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "F: ::serde::Deserialize<'de> + Default")))]
+#[derive(Clone,Debug)]
+pub struct BankRls<F> {
+
+    /// The filter with one output per target.
+    mimo: MimoRls<F>,
+}
+
+impl<F: NdFloat> BankRls<F> {
+
+    /// Constructs a new BankRls object with initialization factor δ for inputs of length
+    /// `n_taps` and `n_targets` targets.
+    pub fn new(initialization_factor: F, forgetting_factor: F, n_taps: usize, n_targets: usize) -> Self {
+        BankRls {
+            mimo: MimoRls::new(initialization_factor, forgetting_factor, n_taps, n_targets),
+        }
+    }
+
+    /// Computes the filter outputs for all targets without updating the filter state.
+    ///
+    /// **Panics** if the length of `input` does not match the number of taps.
+    pub fn predict<S>(&self, input: &ArrayBase<S, Ix1>) -> Array1<F>
+        where S: Data<Elem = F>
+    {
+        self.mimo.predict(input)
+    }
+
+    /// Performs a recursive update of all filters with the common `input` and returns the prior
+    /// errors of all targets, see [`MimoRls::update`](struct.MimoRls.html#method.update).
+    ///
+    /// **Panics** if the length of `input` does not match the number of taps, or if the
+    /// length of `targets` does not match the number of targets.
+    pub fn update_bank<S1, S2>(&mut self, input: &ArrayBase<S1, Ix1>, targets: &ArrayBase<S2, Ix1>)
+        -> &Array1<F>
+        where S1: Data<Elem = F>,
+              S2: Data<Elem = F>,
+    {
+        self.mimo.update(input, targets)
+    }
+}
+
+impl<T> BankRls<T> {
+
+    /// Returns a reference to the weight matrix, with the weight vector of each target as a row.
+    pub fn weight_ref(&self) -> &Array2<T> {
+        self.mimo.weight_ref()
+    }
+
+    /// Returns a reference to the shared inverse correlation matrix.
+    pub fn inverse_correlation_ref(&self) -> &Array2<T> {
+        self.mimo.inverse_correlation_ref()
+    }
+
+    /// Returns a reference to the prior errors of all targets.
+    pub fn prior_error_ref(&self) -> &Array1<T> {
+        self.mimo.prior_error_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Rls;
+    use test_util::Lcg;
+
+    #[test]
+    fn bank_matches_independent_filters() {
+        let mut rng = Lcg::new(92);
+        let mut bank = BankRls::<f64>::new(0.01, 0.98, 3, 4);
+        let mut filters: Vec<_> = (0..4).map(|_| Rls::new(0.01, 0.98, 3)).collect();
+        let true_weight = Array2::from_shape_fn((4, 3), |_| rng.next());
+
+        for _ in 0..200 {
+            let input = rng.array(3);
+            let targets = true_weight.dot(&input) + rng.array(4) * 0.1;
+            let prior_errors = bank.update_bank(&input, &targets);
+            for (j, filter) in filters.iter_mut().enumerate() {
+                assert!((filter.update(&input, targets[j]) - prior_errors[j]).abs() < 1e-10);
+            }
+        }
+
+        for (row, filter) in bank.weight_ref().outer_iter().zip(&filters) {
+            assert!((&row - filter.weight_ref()).iter().all(|x| x.abs() < 1e-10));
+        }
+    }
+
+    #[test]
+    fn update_returns_prior_errors() {
+        let mut mimo = MimoRls::<f64>::new(0.01, 0.98, 2, 2);
+        let input = arr1(&[1.0, 2.0]);
+        assert_eq!(mimo.update(&input, &arr1(&[1.0, -1.0])), &arr1(&[1.0, -1.0]));
+
+        let expected = arr1(&[3.0, 4.0]) - &mimo.predict(&input);
+        let prior_errors = mimo.update_view(input.view(), arr1(&[3.0, 4.0]).view()).clone();
+        assert_eq!(prior_errors, expected);
+        assert_eq!(&prior_errors, mimo.prior_error_ref());
+    }
+}
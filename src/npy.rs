@@ -0,0 +1,126 @@
+use ndarray::prelude::*;
+use ndarray_npy::{NpzReader, NpzWriter, ReadNpzError, ReadableElement, WritableElement, WriteNpzError};
+
+use std::io::{self, Read, Seek, Write};
+
+use Rls;
+
+// The arrays are converted to and from the version of ndarray used by ndarray-npy.
+impl<F> Rls<F>
+    where F: NdFloat + ReadableElement + WritableElement,
+{
+    /// Writes the inverse forgetting factor λ^{-1}, the weight vector w, the inverse correlation
+    /// matrix P, and whether the filter has an [intercept](#method.with_bias) to `writer` as an
+    /// uncompressed npz archive.
+    ///
+    /// The arrays are stored as `inv_forgetting_factor`, `weight`, `inverse_correlation`, and
+    /// `bias`, a `uint8` scalar of 1 with an intercept and 0 without, so that e.g.
+    /// `numpy.load(path)["weight"]` returns the weight vector. The other parameters and the state
+    /// of the filter are not written, see [`read_npz`](#method.read_npz).
+    pub fn write_npz<W: Write + Seek>(&self, writer: W) -> io::Result<()> {
+        let n = self.weight.len();
+
+        let inv_forgetting_factor = npy_ndarray::arr0(self.inv_forgetting_factor);
+        let weight = npy_ndarray::Array1::from_vec(self.weight.to_vec());
+        let inverse_correlation = npy_ndarray::Array2::from_shape_vec(
+            (n, n),
+            self.inverse_correlation.iter().cloned().collect(),
+        ).unwrap();
+        let bias = npy_ndarray::arr0(self.bias as u8);
+
+        let mut npz = NpzWriter::new(writer);
+        npz.add_array("inv_forgetting_factor.npy", &inv_forgetting_factor).map_err(write_error)?;
+        npz.add_array("weight.npy", &weight).map_err(write_error)?;
+        npz.add_array("inverse_correlation.npy", &inverse_correlation).map_err(write_error)?;
+        npz.add_array("bias.npy", &bias).map_err(write_error)?;
+        Ok(())
+    }
+
+    /// Reads a filter previously written by [`write_npz`](#method.write_npz) from `reader`.
+    ///
+    /// The filter is reconstructed by [`from_parts`](#method.from_parts), so apart from λ, w, P,
+    /// and the intercept it starts out with the defaults of a new filter.
+    pub fn read_npz<R: Read + Seek>(reader: R) -> io::Result<Self> {
+        let mut npz = NpzReader::new(reader).map_err(read_error)?;
+
+        let inv_forgetting_factor: npy_ndarray::Array0<F> = npz.by_name("inv_forgetting_factor.npy")
+            .map_err(read_error)?;
+        let weight: npy_ndarray::Array1<F> = npz.by_name("weight.npy").map_err(read_error)?;
+        let inverse_correlation: npy_ndarray::Array2<F> = npz.by_name("inverse_correlation.npy")
+            .map_err(read_error)?;
+        let bias: npy_ndarray::Array0<u8> = npz.by_name("bias.npy").map_err(read_error)?;
+
+        let weight = Array1::from_iter(weight.iter().cloned());
+        let inverse_correlation = Array2::from_shape_vec(
+            inverse_correlation.dim(),
+            inverse_correlation.iter().cloned().collect(),
+        ).unwrap();
+
+        let mut rls = Rls::from_parts(inv_forgetting_factor[()], weight, inverse_correlation)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        match bias[()] {
+            0 => {}
+            1 if !rls.weight.is_empty() => rls.enable_bias(),
+            _ => return Err(io::Error::new(io::ErrorKind::InvalidData, "invalid bias flag")),
+        }
+        Ok(rls)
+    }
+}
+
+fn write_error(error: WriteNpzError) -> io::Error {
+    match error {
+        WriteNpzError::Io(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidData, error.to_string()),
+    }
+}
+
+fn read_error(error: ReadNpzError) -> io::Error {
+    match error {
+        ReadNpzError::Io(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidData, error.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+    use test_util::Lcg;
+
+    fn round_trip(rls: &Rls<f64>) -> Rls<f64> {
+        let mut buffer = Cursor::new(Vec::new());
+        rls.write_npz(&mut buffer).unwrap();
+        buffer.set_position(0);
+        Rls::read_npz(buffer).unwrap()
+    }
+
+    #[test]
+    fn npz_round_trip_keeps_parameters_and_bias() {
+        let mut rng = Lcg::new(63);
+        let mut plain = Rls::<f64>::new(0.1, 0.97, 2);
+        let mut biased = Rls::<f64>::with_bias(0.1, 0.97, 2);
+        for _ in 0..30 {
+            let input = rng.array(2);
+            plain.update(&input, input[0] - input[1]);
+            biased.update(&input, input[0] - input[1] + 0.5);
+        }
+
+        for rls in &[plain, biased] {
+            let mut loaded = round_trip(rls);
+            assert_eq!(loaded.has_bias(), rls.has_bias());
+            assert_eq!(loaded.weight_ref(), rls.weight_ref());
+            assert_eq!(loaded.inverse_correlation_ref(), rls.inverse_correlation_ref());
+            assert_eq!(loaded.forgetting_factor(), rls.forgetting_factor());
+
+            let mut rls = rls.clone();
+            let input = rng.array(2);
+            assert_eq!(loaded.predict(&input), rls.predict(&input));
+            assert_eq!(loaded.update(&input, 1.0), rls.update(&input, 1.0));
+        }
+    }
+
+    #[test]
+    fn read_npz_rejects_garbage() {
+        assert!(Rls::<f64>::read_npz(Cursor::new(vec![1u8, 2, 3])).is_err());
+    }
+}
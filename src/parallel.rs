@@ -0,0 +1,57 @@
+use ndarray::prelude::*;
+use rayon::prelude::*;
+
+use Rls;
+
+/// Updates a bank of independent filters in parallel over a whole sequence of shared inputs.
+///
+/// Each row of `inputs` is the input u(i) of time step i, which is the same for all filters,
+/// and `targets[[i, j]]` is the desired output d(i) of the filter `filters[j]`. Every filter is
+/// updated with all time steps in order, on its own rayon task, so the result is identical to
+/// updating the filters one after the other.
+///
+/// **Panics** if the number of rows of `targets` does not match that of `inputs`, if the
+/// number of columns of `targets` does not match the number of filters, or if an update panics,
+/// see [`Rls::update`](struct.Rls.html#method.update).
+pub fn par_update_bank<F>(filters: &mut [Rls<F>], inputs: &ArrayView2<F>, targets: &ArrayView2<F>)
+    where F: NdFloat + Send + Sync,
+{
+    assert_eq!(targets.rows(), inputs.rows(),
+        "targets with {} rows do not match {} inputs",
+        targets.rows(), inputs.rows());
+    assert_eq!(targets.cols(), filters.len(),
+        "targets with {} columns do not match {} filters",
+        targets.cols(), filters.len());
+
+    filters.par_iter_mut()
+        .enumerate()
+        .for_each(|(j, rls)| {
+            for (input, &target) in inputs.outer_iter().zip(targets.column(j).iter()) {
+                rls.update(&input, target);
+            }
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::Lcg;
+
+    #[test]
+    fn parallel_bank_matches_serial_loop() {
+        let mut rng = Lcg::new(50);
+        let inputs = Array2::from_shape_fn((100, 3), |_| rng.next());
+        let targets = Array2::from_shape_fn((100, 4), |_| rng.next());
+        let mut filters = vec![Rls::<f64>::new(0.1, 0.99, 3); 4];
+        let mut serial = filters.clone();
+
+        par_update_bank(&mut filters, &inputs.view(), &targets.view());
+        for (j, rls) in serial.iter_mut().enumerate() {
+            for (input, &target) in inputs.outer_iter().zip(targets.column(j).iter()) {
+                rls.update(&input, target);
+            }
+        }
+
+        assert_eq!(filters, serial);
+    }
+}
@@ -0,0 +1,69 @@
+use ndarray::prelude::*;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter};
+use std::path::Path;
+
+use Rls;
+
+impl<F> Rls<F>
+    where F: NdFloat + Default + Serialize + DeserializeOwned,
+{
+    /// Saves the filter to the file at `path` in bincode format, creating or truncating it.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        bincode::serialize_into(writer, self).map_err(|e| into_io_error(*e))
+    }
+
+    /// Loads a filter previously written by [`save`](#method.save) from the file at `path`.
+    ///
+    /// The scratch buffers, which are not saved, are reallocated, so the loaded filter can be
+    /// updated right away.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut rls: Rls<F> = bincode::deserialize_from(reader).map_err(|e| into_io_error(*e))?;
+
+        rls.temp_vec = Array1::zeros(rls.weight.len());
+        if rls.bias {
+            rls.bias_input = Array1::zeros(rls.weight.len());
+        }
+        Ok(rls)
+    }
+}
+
+fn into_io_error(error: bincode::ErrorKind) -> io::Error {
+    match error {
+        bincode::ErrorKind::Io(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidData, error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use test_util::Lcg;
+
+    #[test]
+    fn save_and_load_predict_identically() {
+        let mut rng = Lcg::new(41);
+        let mut rls = Rls::<f64>::with_bias(0.1, 0.98, 3);
+        for _ in 0..50 {
+            let input = rng.array(3);
+            let target = 1.5 * input[0] - input[2] + 0.3;
+            rls.update(&input, target);
+        }
+
+        let path = ::std::env::temp_dir().join(format!("recless-save-load-{}.bin", ::std::process::id()));
+        rls.save(&path).unwrap();
+        let mut loaded = Rls::<f64>::load(&path).unwrap();
+        ::std::fs::remove_file(&path).unwrap();
+
+        for _ in 0..10 {
+            let input = rng.array(3);
+            assert_eq!(loaded.predict(&input), rls.predict(&input));
+            assert_eq!(loaded.update(&input, input[1]), rls.update(&input, input[1]));
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use ndarray::prelude::*;
+use rand::Rng;
+
+use {cholesky, Rls};
+
+impl<F: NdFloat> Rls<F> {
+
+    /// Draws a weight vector from the posterior N(w(i), σ² P(i)) implied by the current estimate,
+    /// with σ² the variance of the measurement noise, e.g. the
+    /// [residual variance](#method.residual_variance).
+    ///
+    /// The sample is w(i) + σ L z, with z standard normally distributed and L the lower Cholesky
+    /// factor of P(i), for Thompson-sampling-style exploration or to estimate the predictive
+    /// distribution. Directions in which P(i) has lost its positive definiteness due to
+    /// round-off are not sampled.
+    ///
+    /// **Panics** if `noise_variance` is negative.
+    pub fn sample_weights<R: Rng>(&self, rng: &mut R, noise_variance: F) -> Array1<F> {
+        assert!(noise_variance >= F::zero(),
+            "noise variance must be non-negative, got {}", noise_variance);
+
+        let n = self.weight.len();
+        let factor = cholesky(&self.inverse_correlation, true).unwrap();
+        let normal = Array1::from_iter((0..n).map(|_| standard_normal(rng)));
+
+        let mut sample = self.weight.clone();
+        sample.scaled_add(noise_variance.sqrt(), &factor.dot(&normal));
+        sample
+    }
+}
+
+/// Draws a standard normally distributed sample with the Box-Muller transform.
+fn standard_normal<F: NdFloat, R: Rng>(rng: &mut R) -> F {
+    // The first uniform sample lies in (0, 1], so that its logarithm is finite.
+    let u1 = F::from(1.0 - rng.gen::<f64>()).unwrap();
+    let u2 = F::from(rng.gen::<f64>()).unwrap();
+    let two = F::from(2.0).unwrap();
+    (-two * u1.ln()).sqrt() * (two * F::from(::core::f64::consts::PI).unwrap() * u2).cos()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+    use test_util::Lcg;
+
+    /// Adapts the deterministic test generator to `rand`.
+    struct TestRng(Lcg);
+
+    impl RngCore for TestRng {
+        fn next_u32(&mut self) -> u32 {
+            (self.next_u64() >> 32) as u32
+        }
+
+        fn next_u64(&mut self) -> u64 {
+            ((self.0.next() + 0.5) * (1u64 << 53) as f64) as u64 * (1 << 11)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+            for chunk in dest.chunks_mut(8) {
+                let bytes = self.next_u64().to_le_bytes();
+                chunk.copy_from_slice(&bytes[..chunk.len()]);
+            }
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+            self.fill_bytes(dest);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn sample_moments_converge_to_posterior() {
+        let mut rng = TestRng(Lcg::new(73));
+        let mut rls = Rls::<f64>::new(0.1, 1.0, 2);
+        for _ in 0..50 {
+            let input = rng.0.array(2);
+            rls.update(&input, input[0] - 2.0 * input[1]);
+        }
+
+        let noise_variance = 0.5;
+        let draws = 20000;
+        let samples = (0..draws)
+            .map(|_| rls.sample_weights(&mut rng, noise_variance))
+            .collect::<Vec<_>>();
+        let mean = samples.iter().fold(Array1::zeros(2), |sum, sample| sum + sample) / draws as f64;
+        let covariance = samples.iter().fold(Array2::zeros((2, 2)), |sum, sample| {
+            let centered = (sample - rls.weight_ref()).into_shape((2, 1)).unwrap();
+            sum + centered.dot(&centered.t())
+        }) / draws as f64;
+
+        let expected_covariance = rls.weight_covariance(noise_variance);
+        for i in 0..2 {
+            let standard_error = (expected_covariance[[i, i]] / draws as f64).sqrt();
+            assert!((mean[i] - rls.weight_ref()[i]).abs() < 4.0 * standard_error);
+        }
+        let scale = expected_covariance.diag().scalar_sum();
+        assert!((&covariance - &expected_covariance).iter().all(|d| d.abs() < 0.05 * scale));
+    }
+
+    #[test]
+    fn samples_stay_in_range_of_semidefinite_covariance() {
+        let mut rng = TestRng(Lcg::new(73));
+        let inverse_correlation = arr2(&[[1.0, 1.0], [1.0, 1.0]]);
+        let rls = Rls::<f64>::from_parts(1.0, arr1(&[1.0, 2.0]), inverse_correlation).unwrap();
+        for _ in 0..100 {
+            let sample = rls.sample_weights(&mut rng, 1.0);
+            assert!(((sample[0] - 1.0) - (sample[1] - 2.0)).abs() < 1e-12);
+        }
+    }
+}
@@ -0,0 +1,294 @@
+use ndarray::prelude::*;
+use ndarray::Data;
+use ndarray::linalg::general_mat_vec_mul;
+
+use {RlsError, update_gain, update_inverse_correlation};
+
+/// The parameters of a sliding-window (finite-memory) recursive least squares algorithm.
+///
+/// In addition to the exponential forgetting of [`Rls`](struct.Rls.html), only the last `L`
+/// samples enter the least squares problem: once the window is full, every update first removes
+/// the oldest sample by a rank-1 downdate of the inverse correlation matrix P(i) and then adds
+/// the new sample by the usual rank-1 update. With λ = 1 this fully discards old data, which is
+/// useful for abruptly changing systems.
+///
+/// Since the update adds each sample to the correlation matrix with weight λ², the oldest sample
+/// u of a window of length L carries the weight β = λ^{L+1} when it is removed. The downdate
+/// P ← P + P u uᵀ P / (β^{-1} - u · P u) is only defined for a positive denominator; otherwise
+/// the update is rejected with [`RlsError::NonPositiveDenominator`].
+///
+/// [`RlsError::NonPositiveDenominator`]: enum.RlsError.html#variant.NonPositiveDenominator
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug)]
+pub struct SlidingWindowRls<F> {
+
+    /// The inverse forgetting factor λ^{-1}.
+    inv_forgetting_factor: F,
+
+    /// The gain vector used during the update of the inverse correlation matrix P(i) and the
+    /// (tap) weight vector w(i).
+    gain: Array1<F>,
+
+    /// The *inverse correlation matrix* of the samples in the window, initialized as
+    /// P = δ^{-1} · 𝟙.
+    inverse_correlation: Array2<F>,
+
+    /// The *(tap) weight vector* w(i).
+    weight: Array1<F>,
+
+    /// The prior error, calculated as the difference between the desired output and the filter
+    /// output before an update.
+    prior_error: F,
+
+    /// Ring buffer of the input vectors in the window, one per row.
+    inputs: Array2<F>,
+
+    /// Ring buffer of the targets in the window.
+    targets: Array1<F>,
+
+    /// The position in the ring buffers the next sample is written to. Once the window is full,
+    /// this is also the position of the oldest sample.
+    position: usize,
+
+    /// The number of samples currently in the window.
+    filled: usize,
+
+    // Scratch vector used for the update of the inverse correlation matrix. It is not serialized
+    // and reallocated by the first update after deserialization.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    temp_vec: Array1<F>,
+}
+
+impl<F: NdFloat> SlidingWindowRls<F> {
+
+    /// Constructs a new SlidingWindowRls object with initialization factor δ, a weight vector of
+    /// length n, and a window of the last `window_size` samples.
+    ///
+    /// **Panics** if `window_size` is zero.
+    pub fn new(initialization_factor: F, forgetting_factor: F, n: usize, window_size: usize) -> Self {
+        assert!(window_size > 0, "window size must be positive");
+
+        let one = F::one();
+        let zero = F::zero();
+
+        let inv_forgetting_factor = one / forgetting_factor;
+
+        let gain = Array1::zeros(n);
+        let weight = Array1::zeros(n);
+        let prior_error = zero;
+
+        let mut inverse_correlation = Array2::eye(n);
+        inverse_correlation *= one/initialization_factor;
+
+        let inputs = Array2::zeros([window_size, n]);
+        let targets = Array1::zeros(window_size);
+
+        let temp_vec = Array1::zeros(n);
+
+        SlidingWindowRls {
+            inv_forgetting_factor,
+            gain,
+            inverse_correlation,
+            weight,
+            prior_error,
+            inputs,
+            targets,
+            position: 0,
+            filled: 0,
+            temp_vec,
+        }
+    }
+
+    /// Computes the filter output y(i) = w(i) · u(i) without updating the filter state.
+    ///
+    /// **Panics** if the length of `input` does not match the length of the weight vector.
+    pub fn predict<S>(&self, input: &ArrayBase<S, Ix1>) -> F
+        where S: Data<Elem = F>
+    {
+        assert_eq!(input.len(), self.weight.len(),
+            "input of length {} does not match weight of length {}",
+            input.len(), self.weight.len());
+        self.weight.dot(input)
+    }
+
+    /// Returns the number of samples the window holds at most.
+    pub fn window_size(&self) -> usize {
+        self.targets.len()
+    }
+
+    /// Removes the oldest sample if the window is full and performs a recursive update
+    /// of inverse correlation matrix and weight vector with the new sample.
+    ///
+    /// Returns the prior error.
+    ///
+    /// **Panics** if the update fails, see [`try_update`](#method.try_update).
+    pub fn update<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F) -> F
+        where S: Data<Elem = F>
+    {
+        match self.try_update(input, target) {
+            Ok(prior_error) => prior_error,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Removes the oldest sample if the window is full and performs a recursive update
+    /// of inverse correlation matrix and weight vector with the new sample, returning the
+    /// prior error.
+    ///
+    /// Returns an error without modifying the filter if the length of `input` does not
+    /// match the number of taps, or if the downdate denominator is not positive.
+    pub fn try_update<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F) -> Result<F, RlsError>
+        where S: Data<Elem = F>
+    {
+        if input.len() != self.weight.len() {
+            return Err(RlsError::DimensionMismatch {
+                expected: self.weight.len(),
+                got: input.len(),
+            });
+        }
+
+        // The scratch vector is empty after deserialization.
+        if self.temp_vec.len() != self.weight.len() {
+            self.temp_vec = Array1::zeros(self.weight.len());
+        }
+
+        if self.filled == self.window_size() {
+            let oldest_input = self.inputs.row(self.position);
+            let oldest_target = self.targets[self.position];
+
+            general_mat_vec_mul(
+                F::one(),
+                &self.inverse_correlation,
+                &oldest_input,
+                F::zero(),
+                &mut self.temp_vec
+            );
+
+            // The oldest sample was added with weight λ² and has since been forgotten
+            // L - 1 times.
+            let inv_sample_weight = self.inv_forgetting_factor.powi(self.window_size() as i32 + 1);
+            let c = inv_sample_weight - oldest_input.dot(&self.temp_vec);
+            if c.is_nan() || c <= F::zero() {
+                return Err(RlsError::NonPositiveDenominator);
+            }
+
+            // The downdate is an update with negative gain and without forgetting.
+            self.gain.assign(&self.temp_vec);
+            self.gain /= -c;
+
+            let oldest_error = oldest_target - self.weight.dot(&oldest_input);
+            self.weight.scaled_add(oldest_error, &self.gain);
+
+            update_inverse_correlation(
+                &mut self.inverse_correlation,
+                F::one(),
+                &oldest_input,
+                &self.gain,
+                &mut self.temp_vec,
+            );
+        }
+
+        update_gain(
+            &self.inverse_correlation,
+            self.inv_forgetting_factor,
+            input,
+            &mut self.gain,
+        );
+
+        // Calculate the prior error using the not yet updated tap weight.
+        self.prior_error = target - self.weight.dot(input);
+
+        // Update the tap weight.
+        self.weight.scaled_add(self.prior_error, &self.gain);
+
+        update_inverse_correlation(
+            &mut self.inverse_correlation,
+            self.inv_forgetting_factor,
+            input,
+            &self.gain,
+            &mut self.temp_vec,
+        );
+
+        self.inputs.row_mut(self.position).assign(input);
+        self.targets[self.position] = target;
+        self.position = (self.position + 1) % self.window_size();
+        if self.filled < self.window_size() {
+            self.filled += 1;
+        }
+
+        Ok(self.prior_error)
+    }
+}
+
+impl<T> SlidingWindowRls<T> {
+
+    /// Returns a reference to the gain vector.
+    pub fn gain_ref(&self) -> &Array1<T> {
+        &self.gain
+    }
+
+    /// Returns a reference to the inverse correlation matrix.
+    pub fn inverse_correlation_ref(&self) -> &Array2<T> {
+        &self.inverse_correlation
+    }
+
+    /// Returns a reference to the inverse forgetting factor.
+    pub fn inv_forgetting_factor_ref(&self) -> &T {
+        &self.inv_forgetting_factor
+    }
+
+    /// Returns a reference to the (tap) weight vector.
+    pub fn weight_ref(&self) -> &Array1<T> {
+        &self.weight
+    }
+
+    /// Returns a reference to the prior error.
+    pub fn prior_error_ref(&self) -> &T {
+        &self.prior_error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Rls;
+    use test_util::Lcg;
+
+    /// Runs a sliding-window filter over a random system and compares it with a fresh `Rls`
+    /// fed only the samples in the window.
+    fn compare_with_fresh_filter(initialization_factor: f64, forgetting_factor: f64, tol: f64) {
+        let (n, window_size) = (3, 20);
+        let mut rng = Lcg::new(10);
+        let inputs = Array2::from_shape_fn((100, n), |_| rng.next());
+        let targets = Array1::from_shape_fn(100, |i| {
+            // The system changes halfway, which the window forgets.
+            let weight = if i < 50 { arr1(&[1.0, -2.0, 0.5]) } else { arr1(&[-1.0, 0.0, 3.0]) };
+            weight.dot(&inputs.row(i)) + 0.01 * rng.next()
+        });
+
+        let mut windowed = SlidingWindowRls::new(initialization_factor, forgetting_factor, n, window_size);
+        for (input, &target) in inputs.outer_iter().zip(targets.iter()) {
+            windowed.update(&input, target);
+        }
+
+        let mut fresh = Rls::new(initialization_factor, forgetting_factor, n);
+        for i in 100 - window_size..100 {
+            fresh.update(&inputs.row(i), targets[i]);
+        }
+
+        for (a, b) in windowed.weight_ref().iter().zip(fresh.weight_ref().iter()) {
+            assert!((a - b).abs() < tol, "{} != {}", windowed.weight_ref(), fresh.weight_ref());
+        }
+    }
+
+    #[test]
+    fn matches_fresh_filter_without_forgetting() {
+        compare_with_fresh_filter(0.1, 1.0, 1e-10);
+    }
+
+    #[test]
+    fn matches_fresh_filter_with_forgetting() {
+        // The initialization decays differently in both filters, so it has to be negligible.
+        compare_with_fresh_filter(1e-6, 0.95, 1e-4);
+    }
+}
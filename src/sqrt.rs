@@ -0,0 +1,235 @@
+use ndarray::prelude::*;
+use ndarray::{Data, Zip};
+use ndarray::linalg::general_mat_vec_mul;
+
+/// The parameters of a square-root recursive least squares algorithm.
+///
+/// Instead of the inverse correlation matrix P(i) itself, this propagates a square-root factor
+/// S(i) with P(i) = S(i) S(i)ᵀ, so that P(i) stays positive semidefinite regardless of
+/// round-off. This makes the filter robust on ill-conditioned (e.g. near-collinear) inputs, at
+/// the cost of about twice the work per update of [`Rls`](struct.Rls.html).
+///
+/// Each update applies Givens rotations to the prearray
+///
+/// ```text
+/// ⎡ λ^{-1/2}  u(i)ᵀ S(i-1) ⎤ Θ(i) = ⎡ γ(i)^{1/2}          0             ⎤
+/// ⎣ 0         S(i-1)       ⎦        ⎣ k(i) γ(i)^{1/2}     λ^{1/2} S(i)  ⎦
+/// ```
+///
+/// with γ(i) = λ^{-1} + u(i)ᵀ P(i-1) u(i), which yields the gain vector k(i) and the new factor
+/// S(i) without ever forming P(i).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug)]
+pub struct SqrtRls<F> {
+
+    /// The inverse forgetting factor λ^{-1}.
+    inv_forgetting_factor: F,
+
+    /// The gain vector used during the update of S(i) and the (tap) weight vector w(i).
+    gain: Array1<F>,
+
+    /// The square-root factor S(i) of the inverse correlation matrix, initialized as
+    /// S = δ^{-1/2} · 𝟙.
+    sqrt_inverse_correlation: Array2<F>,
+
+    /// The *(tap) weight vector* w(i).
+    weight: Array1<F>,
+
+    /// The prior error, calculated as the difference between the desired output and the filter
+    /// output before an update.
+    prior_error: F,
+
+    // Scratch vector holding the first row u(i)ᵀ S(i-1) of the prearray. It is not serialized
+    // and reallocated by the first update after deserialization.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    temp_vec: Array1<F>,
+}
+
+impl<F: NdFloat> SqrtRls<F> {
+
+    /// Constructs a new SqrtRls object with initialization factor δ and a weight vector of
+    /// length n.
+    pub fn new(initialization_factor: F, forgetting_factor: F, n: usize) -> Self {
+        let weight = Array1::zeros(n);
+
+        SqrtRls::with_weight(initialization_factor, forgetting_factor, weight)
+    }
+
+    /// Constructs a new SqrtRls object with initialization factor δ and pre-defined weight w.
+    pub fn with_weight(initialization_factor: F, forgetting_factor: F, weight: Array1<F>) -> Self {
+        let one = F::one();
+        let zero = F::zero();
+
+        let n = weight.len();
+
+        let inv_forgetting_factor = one / forgetting_factor;
+
+        let gain = Array1::zeros(n);
+        let prior_error = zero;
+
+        let mut sqrt_inverse_correlation = Array2::eye(n);
+        sqrt_inverse_correlation *= (one/initialization_factor).sqrt();
+
+        let temp_vec = Array1::zeros(n);
+
+        SqrtRls {
+            inv_forgetting_factor,
+            gain,
+            sqrt_inverse_correlation,
+            weight,
+            prior_error,
+            temp_vec,
+        }
+    }
+
+    /// Computes the filter output y(i) = w(i) · u(i) without updating the filter state.
+    ///
+    /// **Panics** if the length of `input` does not match the length of the weight vector.
+    pub fn predict<S>(&self, input: &ArrayBase<S, Ix1>) -> F
+        where S: Data<Elem = F>
+    {
+        assert_eq!(input.len(), self.weight.len(),
+            "input of length {} does not match weight of length {}",
+            input.len(), self.weight.len());
+        self.weight.dot(input)
+    }
+
+    /// Returns the inverse correlation matrix P(i) = S(i) S(i)ᵀ.
+    pub fn inverse_correlation(&self) -> Array2<F> {
+        self.sqrt_inverse_correlation.dot(&self.sqrt_inverse_correlation.t())
+    }
+
+    /// Performs a recursive update of the square-root factor and the weight vector, returning
+    /// the prior error.
+    ///
+    /// **Panics** if the length of `input` does not match the length of the weight vector.
+    pub fn update<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F) -> F
+        where S: Data<Elem = F>
+    {
+        assert_eq!(input.len(), self.weight.len(),
+            "input of length {} does not match weight of length {}",
+            input.len(), self.weight.len());
+
+        // The scratch vector is empty after deserialization.
+        if self.temp_vec.len() != self.weight.len() {
+            self.temp_vec = Array1::zeros(self.weight.len());
+        }
+
+        // The first row of the prearray is (λ^{-1/2}, u(i)ᵀ S(i-1)), the first column is
+        // (λ^{-1/2}, 0) and accumulated in `root` and `gain`.
+        general_mat_vec_mul(
+            F::one(),
+            &self.sqrt_inverse_correlation.t(),
+            input,
+            F::zero(),
+            &mut self.temp_vec
+        );
+        let mut root = self.inv_forgetting_factor.sqrt();
+        self.gain.fill(F::zero());
+
+        // Annihilate the first row against its leading element, one column at a time.
+        for (j, &z) in self.temp_vec.iter().enumerate() {
+            if z == F::zero() {
+                continue;
+            }
+            let rho = root.hypot(z);
+            let cos = root / rho;
+            let sin = z / rho;
+            root = rho;
+
+            Zip::from(&mut self.gain)
+                .and(self.sqrt_inverse_correlation.column_mut(j))
+                .apply(|g, s| {
+                    let (g_old, s_old) = (*g, *s);
+                    *g = cos * g_old + sin * s_old;
+                    *s = cos * s_old - sin * g_old;
+                });
+        }
+
+        // The first column of the postarray holds k(i) γ(i)^{1/2}, with γ(i)^{1/2} = root.
+        self.gain /= root;
+        self.sqrt_inverse_correlation *= self.inv_forgetting_factor.sqrt();
+
+        // Calculate the prior error using the not yet updated tap weight.
+        self.prior_error = target - self.weight.dot(input);
+
+        // Update the tap weight.
+        self.weight.scaled_add(self.prior_error, &self.gain);
+
+        self.prior_error
+    }
+}
+
+impl<T> SqrtRls<T> {
+
+    /// Returns a reference to the gain vector.
+    pub fn gain_ref(&self) -> &Array1<T> {
+        &self.gain
+    }
+
+    /// Returns a reference to the square-root factor S(i) of the inverse correlation matrix.
+    pub fn sqrt_inverse_correlation_ref(&self) -> &Array2<T> {
+        &self.sqrt_inverse_correlation
+    }
+
+    /// Returns a reference to the inverse forgetting factor.
+    pub fn inv_forgetting_factor_ref(&self) -> &T {
+        &self.inv_forgetting_factor
+    }
+
+    /// Returns a reference to the (tap) weight vector.
+    pub fn weight_ref(&self) -> &Array1<T> {
+        &self.weight
+    }
+
+    /// Returns a reference to the prior error.
+    pub fn prior_error_ref(&self) -> &T {
+        &self.prior_error
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Rls;
+    use test_util::Lcg;
+
+    #[test]
+    fn matches_rls_on_well_conditioned_inputs() {
+        let mut rng = Lcg::new(27);
+        let mut rls = Rls::new(0.1, 0.98, 4);
+        let mut sqrt = SqrtRls::new(0.1, 0.98, 4);
+        for _ in 0..200 {
+            let input = rng.array(4);
+            let target = input[0] - 2.0 * input[3] + 0.01 * rng.next();
+            assert!((rls.update(&input, target) - sqrt.update(&input, target)).abs() < 1e-9);
+        }
+        for (a, b) in rls.weight_ref().iter().zip(sqrt.weight_ref().iter()) {
+            assert!((a - b).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn stays_stable_on_near_collinear_inputs() {
+        let mut rng = Lcg::new(7);
+        let mut rls = Rls::new(1e-2f32, 0.9, 3);
+        let mut sqrt = SqrtRls::new(1e-2f32, 0.9, 3);
+        let mut max_rls_error = 0.0f32;
+        let mut max_sqrt_error = 0.0f32;
+        for i in 0..5000 {
+            let x = rng.next() as f32;
+            let input = arr1(&[x, x + 1e-4 * rng.next() as f32, x]);
+            let rls_error = rls.update(&input, 2.0 * x);
+            let sqrt_error = sqrt.update(&input, 2.0 * x);
+            if i > 100 {
+                max_rls_error = max_rls_error.max(rls_error.abs());
+                max_sqrt_error = max_sqrt_error.max(sqrt_error.abs());
+            }
+        }
+        // The inverse correlation matrix of the plain filter loses its positive definiteness and
+        // the filter diverges, while the square-root factorization keeps tracking the target.
+        assert!(max_rls_error > 1.0);
+        assert!(max_sqrt_error < 1e-3);
+        assert!(sqrt.inverse_correlation().diag().iter().all(|&p| p >= 0.0));
+    }
+}
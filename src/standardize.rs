@@ -0,0 +1,274 @@
+use ndarray::prelude::*;
+use ndarray::{Data, Zip};
+
+use Rls;
+
+/// A running standardization of tap inputs to zero mean and unit variance.
+///
+/// The mean μ and variance σ² of each tap are tracked with Welford's algorithm over all inputs
+/// [observed](#method.observe) so far. Taps without any variance yet, e.g. before the second
+/// observation or for a constant input, are centered but not scaled.
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone,Debug,PartialEq)]
+pub struct Standardizer<F> {
+
+    /// The number of observed inputs.
+    count: F,
+
+    /// The running means μ of the taps.
+    mean: Array1<F>,
+
+    /// The running sums of squared deviations from the mean of the taps.
+    sum_of_squares: Array1<F>,
+}
+
+impl<F: NdFloat> Standardizer<F> {
+
+    /// Constructs a new Standardizer for inputs of length n.
+    pub fn new(n: usize) -> Self {
+        Standardizer {
+            count: F::zero(),
+            mean: Array1::zeros(n),
+            sum_of_squares: Array1::zeros(n),
+        }
+    }
+
+    /// Includes `input` in the running means and variances.
+    ///
+    /// **Panics** if the length of `input` does not match the number of taps.
+    pub fn observe<S>(&mut self, input: &ArrayBase<S, Ix1>)
+        where S: Data<Elem = F>
+    {
+        assert_eq!(input.len(), self.mean.len(),
+            "input of length {} does not match standardizer of length {}",
+            input.len(), self.mean.len());
+        self.count += F::one();
+        let count = self.count;
+        Zip::from(&mut self.mean)
+            .and(&mut self.sum_of_squares)
+            .and(input)
+            .apply(|mean, sum_of_squares, &x| {
+                let delta = x - *mean;
+                *mean += delta / count;
+                *sum_of_squares += delta * (x - *mean);
+            });
+    }
+
+    /// Returns the standardized input (u - μ) / σ, tap by tap.
+    ///
+    /// **Panics** if the length of `input` does not match the number of taps.
+    pub fn transform<S>(&self, input: &ArrayBase<S, Ix1>) -> Array1<F>
+        where S: Data<Elem = F>
+    {
+        assert_eq!(input.len(), self.mean.len(),
+            "input of length {} does not match standardizer of length {}",
+            input.len(), self.mean.len());
+        let scale = self.scale();
+        let mut output = input.to_owned();
+        Zip::from(&mut output)
+            .and(&self.mean)
+            .and(&scale)
+            .apply(|x, &mean, &scale| *x = (*x - mean) / scale);
+        output
+    }
+
+    /// Maps weights w learned on standardized inputs back to the original inputs, returning the
+    /// weights w / σ and the intercept -Σ w μ / σ, so that w · (u - μ) / σ equals
+    /// (w / σ) · u - Σ w μ / σ.
+    ///
+    /// **Panics** if the length of `weight` does not match the number of taps.
+    pub fn unscale_weights<S>(&self, weight: &ArrayBase<S, Ix1>) -> (Array1<F>, F)
+        where S: Data<Elem = F>
+    {
+        assert_eq!(weight.len(), self.mean.len(),
+            "weight of length {} does not match standardizer of length {}",
+            weight.len(), self.mean.len());
+        let mut unscaled = weight.to_owned();
+        unscaled /= &self.scale();
+        let intercept = -unscaled.dot(&self.mean);
+        (unscaled, intercept)
+    }
+
+    /// Returns the population variances σ² of the taps, which are zero before the first
+    /// observation.
+    pub fn variance(&self) -> Array1<F> {
+        if self.count == F::zero() {
+            return Array1::zeros(self.mean.len());
+        }
+        let count = self.count;
+        self.sum_of_squares.mapv(|s| s / count)
+    }
+
+    /// Discards all observations.
+    pub(crate) fn clear(&mut self) {
+        self.count = F::zero();
+        self.mean.fill(F::zero());
+        self.sum_of_squares.fill(F::zero());
+    }
+
+    /// Appends `extra` taps without any observations, which hence count as having zero mean
+    /// and variance so far.
+    pub(crate) fn grow(&mut self, extra: usize) {
+        let n = self.mean.len();
+        let mut mean = Array1::zeros(n + extra);
+        mean.view_mut().split_at(Axis(0), n).0.assign(&self.mean);
+        self.mean = mean;
+        let mut sum_of_squares = Array1::zeros(n + extra);
+        sum_of_squares.view_mut().split_at(Axis(0), n).0.assign(&self.sum_of_squares);
+        self.sum_of_squares = sum_of_squares;
+    }
+
+    /// Keeps only the taps at `indices`.
+    pub(crate) fn select(&mut self, indices: &[usize]) {
+        self.mean = self.mean.select(Axis(0), indices);
+        self.sum_of_squares = self.sum_of_squares.select(Axis(0), indices);
+    }
+
+    /// Converts the statistics element-wise to `G`.
+    pub(crate) fn cast<G: NdFloat>(&self) -> Standardizer<G> {
+        let cast = |x: F| G::from(x).unwrap();
+        Standardizer {
+            count: cast(self.count),
+            mean: self.mean.mapv(cast),
+            sum_of_squares: self.sum_of_squares.mapv(cast),
+        }
+    }
+
+    /// Returns the standard deviations used for scaling, with 1 in place of a zero.
+    fn scale(&self) -> Array1<F> {
+        self.variance().mapv(|v| if v > F::zero() { v.sqrt() } else { F::one() })
+    }
+}
+
+impl<T> Standardizer<T> {
+
+    /// Returns a reference to the running means of the taps.
+    pub fn mean_ref(&self) -> &Array1<T> {
+        &self.mean
+    }
+
+    /// Returns the number of taps.
+    pub fn len(&self) -> usize {
+        self.mean.len()
+    }
+
+    /// Returns `true` if the standardizer has no taps.
+    pub fn is_empty(&self) -> bool {
+        self.mean.is_empty()
+    }
+}
+
+/// A recursive least squares filter operating on standardized inputs, see
+/// [`Standardizer`](struct.Standardizer.html).
+///
+/// Inputs with very different scales make the inverse correlation matrix ill-conditioned and
+/// its initialization δ^{-1} · 𝟙 a poor prior for all but one of them. This wrapper standardizes
+/// each input with the running statistics before it reaches the wrapped
+/// [`Rls`](struct.Rls.html). Since centering removes the constant component of the inputs, the
+/// wrapped filter has an [intercept](struct.Rls.html#method.with_bias).
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound(deserialize = "F: ::serde::Deserialize<'de> + Default")))]
+#[derive(Clone,Debug)]
+pub struct StandardizedRls<F> {
+
+    /// The running standardization of the inputs.
+    standardizer: Standardizer<F>,
+
+    /// The filter operating on the standardized inputs.
+    rls: Rls<F>,
+}
+
+impl<F: NdFloat> StandardizedRls<F> {
+
+    /// Constructs a new StandardizedRls object with initialization factor δ for inputs of
+    /// length n.
+    pub fn new(initialization_factor: F, forgetting_factor: F, n: usize) -> Self {
+        StandardizedRls {
+            standardizer: Standardizer::new(n),
+            rls: Rls::with_bias(initialization_factor, forgetting_factor, n),
+        }
+    }
+
+    /// Computes the filter output for `input` without updating the filter state or the
+    /// running statistics.
+    ///
+    /// **Panics** if the length of `input` does not match the number of taps.
+    pub fn predict<S>(&self, input: &ArrayBase<S, Ix1>) -> F
+        where S: Data<Elem = F>
+    {
+        self.rls.predict(&self.standardizer.transform(input))
+    }
+
+    /// Includes `input` in the running statistics and performs a recursive update of the
+    /// wrapped filter with the standardized input, returning the prior error.
+    ///
+    /// **Panics** if the length of `input` does not match the number of taps, or if the update
+    /// fails, see [`Rls::try_update`](struct.Rls.html#method.try_update).
+    pub fn update<S>(&mut self, input: &ArrayBase<S, Ix1>, target: F) -> F
+        where S: Data<Elem = F>
+    {
+        self.standardizer.observe(input);
+        let standardized = self.standardizer.transform(input);
+        self.rls.update(&standardized, target)
+    }
+
+    /// Returns the weights and the intercept with respect to the original inputs, see
+    /// [`Standardizer::unscale_weights`](struct.Standardizer.html#method.unscale_weights).
+    pub fn unscale_weights(&self) -> (Array1<F>, F) {
+        let (intercept, weight) = self.rls.weight_ref().view().split_at(Axis(0), 1);
+        let (unscaled, offset) = self.standardizer.unscale_weights(&weight);
+        (unscaled, intercept[0] + offset)
+    }
+}
+
+impl<T> StandardizedRls<T> {
+
+    /// Returns a reference to the running standardization of the inputs.
+    pub fn standardizer_ref(&self) -> &Standardizer<T> {
+        &self.standardizer
+    }
+
+    /// Returns a reference to the filter operating on the standardized inputs.
+    pub fn rls_ref(&self) -> &Rls<T> {
+        &self.rls
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Rls;
+    use test_util::Lcg;
+
+    #[test]
+    fn standardization_speeds_up_convergence_on_disparate_scales() {
+        let mut rng = Lcg::new(71);
+        let mut sample = || {
+            let input = arr1(&[1000.0 * rng.next(), 1e-3 * rng.next() + 0.01]);
+            let target = 2e-3 * input[0] + 500.0 * input[1] + 1.0;
+            (input, target)
+        };
+
+        // Forgetting discards the samples standardized with the early, inaccurate statistics.
+        let mut standardized = StandardizedRls::<f64>::new(0.1, 0.99, 2);
+        let mut raw = Rls::<f64>::with_bias(0.1, 0.99, 2);
+        for _ in 0..1000 {
+            let (input, target) = sample();
+            standardized.update(&input, target);
+            raw.update(&input, target);
+        }
+
+        let (mut standardized_error, mut raw_error) = (0.0, 0.0);
+        for _ in 0..100 {
+            let (input, target) = sample();
+            standardized_error += (target - standardized.predict(&input)).powi(2);
+            raw_error += (target - raw.predict(&input)).powi(2);
+        }
+        assert!(standardized_error < 1e-2 * raw_error);
+
+        let (weight, intercept) = standardized.unscale_weights();
+        assert!((weight[0] - 2e-3).abs() < 2e-5);
+        assert!((weight[1] - 500.0).abs() < 2.0);
+        assert!((intercept - 1.0).abs() < 2e-2);
+    }
+}
@@ -0,0 +1,21 @@
+use ndarray::prelude::*;
+
+/// A linear congruential generator of deterministic pseudo-random samples for the tests.
+pub struct Lcg(u64);
+
+impl Lcg {
+    pub fn new(seed: u64) -> Self {
+        Lcg(seed)
+    }
+
+    /// Returns a sample uniformly distributed in [-0.5, 0.5).
+    pub fn next(&mut self) -> f64 {
+        self.0 = self.0.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+        (self.0 >> 11) as f64 / (1u64 << 53) as f64 - 0.5
+    }
+
+    /// Returns a vector of n samples.
+    pub fn array(&mut self, n: usize) -> Array1<f64> {
+        Array1::from_iter((0..n).map(|_| self.next()))
+    }
+}
@@ -0,0 +1,103 @@
+//! Checks that the recursive updates do not allocate once the filter is warmed up.
+//!
+//! This lives in its own test binary because it replaces the global allocator.
+
+extern crate ndarray;
+extern crate recless;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use ndarray::{Array1, arr1};
+use recless::{MimoRls, Rls};
+
+/// Forwards to the system allocator and counts the allocations of the current thread, so that
+/// the test harness running on other threads does not interfere.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(|n| n.get())
+}
+
+const TAPS: usize = 8;
+
+/// Runs `update` once to warm up the filter and returns the number of allocations of the
+/// following updates.
+fn steady_state_allocations<U>(mut rls: Rls<f64>, update: U) -> usize
+    where U: Fn(&mut Rls<f64>, &Array1<f64>, f64)
+{
+    let inputs: Vec<_> = (0..100)
+        .map(|i| Array1::from_shape_fn(TAPS, |j| ((i * TAPS + j) as f64).sin()))
+        .collect();
+
+    update(&mut rls, &inputs[0], 1.0);
+    let before = allocations();
+    for (i, input) in inputs.iter().enumerate().skip(1) {
+        update(&mut rls, input, (i as f64).cos());
+    }
+    allocations() - before
+}
+
+#[test]
+fn steady_state_updates_do_not_allocate() {
+    let update = |rls: &mut Rls<f64>, input: &Array1<f64>, target| { rls.update(input, target); };
+
+    assert_eq!(steady_state_allocations(Rls::new(0.01, 0.99, TAPS), update), 0);
+    assert_eq!(steady_state_allocations(Rls::with_bias(0.01, 0.99, TAPS), update), 0);
+    assert_eq!(steady_state_allocations(Rls::with_leakage(0.01, 0.99, TAPS, 0.01), update), 0);
+    assert_eq!(steady_state_allocations(Rls::with_regularization(0.01, 0.99, TAPS, 0.1), update), 0);
+
+    let mut rls = Rls::new(0.01, 0.99, TAPS);
+    rls.set_vff_policy(0.9, 0.999, 1.0);
+    assert_eq!(steady_state_allocations(rls, update), 0);
+
+    let mut rls = Rls::new(0.01, 0.99, TAPS);
+    rls.set_min_denominator(1e-9);
+    assert_eq!(steady_state_allocations(rls, update), 0);
+
+    let mut rls = Rls::new(0.01, 0.99, TAPS);
+    rls.set_symmetrize_every(3);
+    assert_eq!(steady_state_allocations(rls, update), 0);
+
+    assert_eq!(steady_state_allocations(Rls::new(0.01, 0.99, TAPS),
+        |rls, input, target| { rls.update_view(input.view(), target); }), 0);
+    assert_eq!(steady_state_allocations(Rls::new(0.01, 0.99, TAPS),
+        |rls, input, target| { rls.try_update(input, target).unwrap(); }), 0);
+    assert_eq!(steady_state_allocations(Rls::new(0.01, 0.99, TAPS),
+        |rls, input, target| { rls.update_directional(input, target); }), 0);
+    assert_eq!(steady_state_allocations(Rls::new(0.01, 0.99, TAPS),
+        |rls, input, target| { rls.update_robust(input, target, 0.1); }), 0);
+}
+
+#[test]
+fn steady_state_multi_output_updates_do_not_allocate() {
+    let mut mimo = MimoRls::<f64>::new(0.01, 0.99, TAPS, 3);
+    let input = Array1::from_elem(TAPS, 0.5);
+    let target = arr1(&[1.0, 2.0, 3.0]);
+    mimo.update(&input, &target);
+
+    let before = allocations();
+    for _ in 0..50 {
+        mimo.update(&input, &target);
+        mimo.update_view(input.view(), target.view());
+    }
+    assert_eq!(allocations() - before, 0);
+}
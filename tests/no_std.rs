@@ -0,0 +1,27 @@
+//! Checks that the core filter API is usable from a `no_std` crate, and with
+//! `cargo test --no-default-features --test no_std` that the crate builds without `std`.
+
+#![no_std]
+
+// Only for the test harness.
+extern crate std;
+
+extern crate ndarray;
+extern crate recless;
+
+use ndarray::arr1;
+use recless::{Rls, RlsError};
+
+#[test]
+fn core_api_needs_no_std() {
+    let mut rls = Rls::new(0.01, 1.0, 2);
+    let input = arr1(&[1.0, 2.0]);
+
+    assert_eq!(rls.update(&input, 3.0), 3.0);
+    for _ in 0..10 {
+        rls.update(&input, 3.0);
+    }
+    assert!((rls.predict(&input) - 3.0f64).abs() < 1e-3);
+
+    assert_eq!(Rls::<f64>::try_new(0.01, 1.5, 2).unwrap_err(), RlsError::InvalidForgettingFactor);
+}